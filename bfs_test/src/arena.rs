@@ -0,0 +1,855 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+// Tree with the same interface as Tree, but the nodes live next to each other
+// in one Vec and link to each other by index. Freed places are reused before
+// the Vec grows, and dropping the tree is dropping the Vec.
+pub struct ArenaTree<T: Ord> {
+    nodes: Vec<Option<ArenaNode<T>>>,
+    // indices of the None places in nodes
+    free: Vec<usize>,
+    root: Option<usize>,
+    len: usize,
+}
+
+struct ArenaNode<T> {
+    value: T,
+    // number of nodes in the subtree, the node included
+    size: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+// where the index of a node is stored
+#[derive(Copy, Clone)]
+enum Link {
+    Root,
+    Left(usize),
+    Right(usize),
+}
+
+impl<T: Ord> ArenaTree<T> {
+    pub fn from(value: T) -> ArenaTree<T> {
+        let mut tree = ArenaTree::empty();
+        tree.add(value);
+        tree
+    }
+
+    pub fn empty() -> ArenaTree<T> {
+        ArenaTree {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: None,
+            len: 0,
+        }
+    }
+
+    // room for that many values without reallocating
+    pub fn with_capacity(capacity: usize) -> ArenaTree<T> {
+        ArenaTree {
+            nodes: Vec::with_capacity(capacity),
+            ..ArenaTree::empty()
+        }
+    }
+
+    pub fn add(&mut self, value: T) {
+        let link = match self.find(&value) {
+            Ok(_) => return,
+            Err(link) => link,
+        };
+
+        // every node on the way gets one more descendant
+        let mut index = self.root;
+        while let Some(current) = index {
+            let node = self.node_mut(current);
+            node.size += 1;
+            index = if value < node.value {
+                node.left
+            } else {
+                node.right
+            };
+        }
+
+        let node = ArenaNode {
+            value,
+            size: 1,
+            left: None,
+            right: None,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+        self.set_link(link, Some(index));
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains<Q: ?Sized + Ord>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.find(value).is_ok()
+    }
+
+    pub fn get<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        let (_, index) = self.find(value).ok()?;
+        Some(&self.node(index).value)
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        let (_, index) = self.leftmost(Link::Root)?;
+        Some(&self.node(index).value)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        let mut index = self.root?;
+        while let Some(right) = self.node(index).right {
+            index = right;
+        }
+
+        Some(&self.node(index).value)
+    }
+
+    // largest value less than or equal to the given one
+    pub fn floor<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        let mut floor = None;
+        let mut index = self.root;
+        while let Some(current) = index {
+            let node = self.node(current);
+            index = match value.cmp(node.value.borrow()) {
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Less => node.left,
+                Ordering::Greater => {
+                    floor = Some(&node.value);
+                    node.right
+                }
+            };
+        }
+
+        floor
+    }
+
+    // smallest value greater than or equal to the given one
+    pub fn ceiling<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        let mut ceiling = None;
+        let mut index = self.root;
+        while let Some(current) = index {
+            let node = self.node(current);
+            index = match value.cmp(node.value.borrow()) {
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Less => {
+                    ceiling = Some(&node.value);
+                    node.left
+                }
+                Ordering::Greater => node.right,
+            };
+        }
+
+        ceiling
+    }
+
+    pub fn remove<Q: ?Sized + Ord>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+    {
+        let (link, index) = self.find(value).ok()?;
+        self.shrink_path(value);
+        Some(self.remove_at(link, index))
+    }
+
+    // k-th smallest value, counted from 0
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut index = self.root;
+        while let Some(current) = index {
+            let node = self.node(current);
+            let left = self.size(node.left);
+            index = match k.cmp(&left) {
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Less => node.left,
+                Ordering::Greater => {
+                    k -= left + 1;
+                    node.right
+                }
+            };
+        }
+
+        None
+    }
+
+    // number of values less than the given one, the index it has or would have
+    pub fn rank<Q: ?Sized + Ord>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        let mut rank = 0;
+        let mut index = self.root;
+        while let Some(current) = index {
+            let node = self.node(current);
+            index = match value.cmp(node.value.borrow()) {
+                Ordering::Equal => return rank + self.size(node.left),
+                Ordering::Less => node.left,
+                Ordering::Greater => {
+                    rank += self.size(node.left) + 1;
+                    node.right
+                }
+            };
+        }
+
+        rank
+    }
+
+    // moves the values greater than or equal to the given one into a new
+    // tree; they have to move to another Vec anyway, so they are taken from
+    // the end one by one and the new tree is built balanced
+    pub fn split_off<Q: ?Sized + Ord>(&mut self, value: &Q) -> ArenaTree<T>
+    where
+        T: Borrow<Q>,
+    {
+        let mut greater = Vec::new();
+        while self.last().is_some_and(|last| last.borrow() >= value) {
+            greater.extend(self.pop_max());
+        }
+        greater.reverse();
+
+        ArenaTree::from_sorted(greater)
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        let (link, index) = self.shrink_leftmost(Link::Root)?;
+        Some(self.remove_at(link, index))
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        let mut link = Link::Root;
+        let mut index = self.root?;
+        while let Some(right) = self.node(index).right {
+            self.node_mut(index).size -= 1;
+            link = Link::Right(index);
+            index = right;
+        }
+
+        Some(self.remove_at(link, index))
+    }
+
+    // number of levels, 0 for an empty tree
+    pub fn height(&self) -> usize {
+        self.levels().count()
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+        self.root = None;
+        self.len = 0;
+    }
+
+    // values are in order and without duplicates, subtree sizes are right
+    pub fn is_valid(&self) -> bool {
+        self.iter().zip(self.iter().skip(1)).all(|(a, b)| a < b)
+            && self.size(self.root) == self.len
+            && self
+                .nodes
+                .iter()
+                .flatten()
+                .all(|node| node.size == 1 + self.size(node.left) + self.size(node.right))
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter {
+            tree: self,
+            stack: Vec::new(),
+        };
+        iter.push_left(self.root);
+        iter
+    }
+
+    // e.g. tree.range(10..20), like Tree::range
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T, Q, R>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        let mut iter = Range {
+            tree: self,
+            stack: Vec::new(),
+            range,
+            key: PhantomData,
+        };
+        iter.push_left(self.root);
+        iter
+    }
+
+    pub fn preorder(&self) -> Preorder<'_, T> {
+        Preorder {
+            tree: self,
+            stack: self.root.into_iter().collect(),
+        }
+    }
+
+    pub fn postorder(&self) -> Postorder<'_, T> {
+        Postorder {
+            tree: self,
+            stack: self.root.map(|root| (root, false)).into_iter().collect(),
+        }
+    }
+
+    pub fn bfs(&self) -> impl Iterator<Item = &T> {
+        self.bfs_with_depth().map(|(_, value)| value)
+    }
+
+    pub fn bfs_with_depth(&self) -> Bfs<'_, T> {
+        Bfs {
+            tree: self,
+            queue: self.root.map(|root| (0, root)).into_iter().collect(),
+        }
+    }
+
+    pub fn levels(&self) -> Levels<'_, T> {
+        Levels {
+            tree: self,
+            level: self.root.into_iter().collect(),
+        }
+    }
+
+    // the values have to be in order and without duplicates
+    fn from_sorted(values: Vec<T>) -> ArenaTree<T> {
+        let len = values.len();
+        let mut tree = ArenaTree {
+            nodes: values
+                .into_iter()
+                .map(|value| {
+                    Some(ArenaNode {
+                        value,
+                        size: 1,
+                        left: None,
+                        right: None,
+                    })
+                })
+                .collect(),
+            free: Vec::new(),
+            root: None,
+            len,
+        };
+        tree.root = tree.link_balanced(0, len);
+        tree
+    }
+
+    // links the nodes at start..end into a subtree with the smallest possible
+    // height; the recursion is only as deep as the result
+    fn link_balanced(&mut self, start: usize, end: usize) -> Option<usize> {
+        if start == end {
+            return None;
+        }
+
+        let middle = start + (end - start) / 2;
+        let left = self.link_balanced(start, middle);
+        let right = self.link_balanced(middle + 1, end);
+        let node = self.node_mut(middle);
+        node.size = end - start;
+        node.left = left;
+        node.right = right;
+        Some(middle)
+    }
+
+    fn size(&self, index: Option<usize>) -> usize {
+        index.map_or(0, |index| self.node(index).size)
+    }
+
+    fn node(&self, index: usize) -> &ArenaNode<T> {
+        self.nodes[index].as_ref().unwrap()
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut ArenaNode<T> {
+        self.nodes[index].as_mut().unwrap()
+    }
+
+    fn get_link(&self, link: Link) -> Option<usize> {
+        match link {
+            Link::Root => self.root,
+            Link::Left(parent) => self.node(parent).left,
+            Link::Right(parent) => self.node(parent).right,
+        }
+    }
+
+    fn set_link(&mut self, link: Link, index: Option<usize>) {
+        match link {
+            Link::Root => self.root = index,
+            Link::Left(parent) => self.node_mut(parent).left = index,
+            Link::Right(parent) => self.node_mut(parent).right = index,
+        }
+    }
+
+    // the node with the value and where it is linked from,
+    // or the empty link where the value belongs
+    fn find<Q: ?Sized + Ord>(&self, value: &Q) -> Result<(Link, usize), Link>
+    where
+        T: Borrow<Q>,
+    {
+        let mut link = Link::Root;
+        while let Some(index) = self.get_link(link) {
+            let node = self.node(index);
+            link = match value.cmp(node.value.borrow()) {
+                Ordering::Equal => return Ok((link, index)),
+                Ordering::Less => Link::Left(index),
+                Ordering::Greater => Link::Right(index),
+            };
+        }
+
+        Err(link)
+    }
+
+    // smallest node in the subtree at the link
+    fn leftmost(&self, mut link: Link) -> Option<(Link, usize)> {
+        let mut index = self.get_link(link)?;
+        while let Some(left) = self.node(index).left {
+            link = Link::Left(index);
+            index = left;
+        }
+
+        Some((link, index))
+    }
+
+    // like leftmost, the nodes above the smallest one lose a descendant
+    fn shrink_leftmost(&mut self, mut link: Link) -> Option<(Link, usize)> {
+        let mut index = self.get_link(link)?;
+        while let Some(left) = self.node(index).left {
+            self.node_mut(index).size -= 1;
+            link = Link::Left(index);
+            index = left;
+        }
+
+        Some((link, index))
+    }
+
+    // the nodes above the one with the value lose a descendant
+    fn shrink_path<Q: ?Sized + Ord>(&mut self, value: &Q)
+    where
+        T: Borrow<Q>,
+    {
+        let mut index = self.root;
+        while let Some(current) = index {
+            let node = self.node_mut(current);
+            index = match value.cmp(node.value.borrow()) {
+                Ordering::Equal => break,
+                Ordering::Less => node.left,
+                Ordering::Greater => node.right,
+            };
+            node.size -= 1;
+        }
+    }
+
+    // the in-order successor takes the place of a node with both children,
+    // the sizes above the node have to be updated already
+    fn remove_at(&mut self, link: Link, index: usize) -> T {
+        let (left, right) = {
+            let node = self.node(index);
+            (node.left, node.right)
+        };
+
+        let (removed, value) = match (left, right) {
+            (None, child) | (child, None) => {
+                self.set_link(link, child);
+                (index, self.nodes[index].take().unwrap().value)
+            }
+            (Some(_), Some(_)) => {
+                self.node_mut(index).size -= 1;
+                let (successor_link, successor) = self.shrink_leftmost(Link::Right(index)).unwrap();
+                let successor_right = self.node(successor).right;
+                self.set_link(successor_link, successor_right);
+                let successor_value = self.nodes[successor].take().unwrap().value;
+                let value = std::mem::replace(&mut self.node_mut(index).value, successor_value);
+                (successor, value)
+            }
+        };
+
+        self.free.push(removed);
+        self.len -= 1;
+        value
+    }
+}
+
+// in-order, smallest value first
+pub struct Iter<'a, T: Ord> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<usize>,
+}
+
+impl<'a, T: Ord> Iter<'a, T> {
+    fn push_left(&mut self, mut index: Option<usize>) {
+        while let Some(current) = index {
+            self.stack.push(current);
+            index = self.tree.node(current).left;
+        }
+    }
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.stack.pop()?;
+        let node = self.tree.node(index);
+        self.push_left(node.right);
+
+        Some(&node.value)
+    }
+}
+
+// in-order within the bounds, subtrees outside of them are never entered
+pub struct Range<'a, T: Ord, Q: ?Sized, R> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<usize>,
+    range: R,
+    key: PhantomData<fn(&Q)>,
+}
+
+impl<'a, T, Q, R> Range<'a, T, Q, R>
+where
+    T: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    fn below_start(&self, value: &T) -> bool {
+        match self.range.start_bound() {
+            Bound::Included(start) => value.borrow() < start,
+            Bound::Excluded(start) => value.borrow() <= start,
+            Bound::Unbounded => false,
+        }
+    }
+
+    fn after_end(&self, value: &T) -> bool {
+        match self.range.end_bound() {
+            Bound::Included(end) => value.borrow() > end,
+            Bound::Excluded(end) => value.borrow() >= end,
+            Bound::Unbounded => false,
+        }
+    }
+
+    // a node below the start is skipped together with its left subtree
+    fn push_left(&mut self, mut index: Option<usize>) {
+        while let Some(current) = index {
+            let node = self.tree.node(current);
+            if self.below_start(&node.value) {
+                index = node.right;
+            } else {
+                self.stack.push(current);
+                index = node.left;
+            }
+        }
+    }
+}
+
+impl<'a, T, Q, R> Iterator for Range<'a, T, Q, R>
+where
+    T: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.tree.node(self.stack.pop()?);
+        // everything that would follow is after the end too
+        if self.after_end(&node.value) {
+            self.stack.clear();
+            return None;
+        }
+        self.push_left(node.right);
+
+        Some(&node.value)
+    }
+}
+
+// node before its subtrees
+pub struct Preorder<'a, T: Ord> {
+    tree: &'a ArenaTree<T>,
+    stack: Vec<usize>,
+}
+
+impl<'a, T: Ord> Iterator for Preorder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.tree.node(self.stack.pop()?);
+        // the left subtree has to come out first
+        self.stack.extend(node.right);
+        self.stack.extend(node.left);
+
+        Some(&node.value)
+    }
+}
+
+// subtrees before their node
+pub struct Postorder<'a, T: Ord> {
+    tree: &'a ArenaTree<T>,
+    // the flag tells whether the children were already pushed
+    stack: Vec<(usize, bool)>,
+}
+
+impl<'a, T: Ord> Iterator for Postorder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (index, expanded) = self.stack.pop()?;
+            let node = self.tree.node(index);
+            if expanded {
+                return Some(&node.value);
+            }
+
+            self.stack.push((index, true));
+            self.stack.extend(node.right.map(|right| (right, false)));
+            self.stack.extend(node.left.map(|left| (left, false)));
+        }
+    }
+}
+
+// level-order, the root first, then its children from the left, ...
+pub struct Bfs<'a, T: Ord> {
+    tree: &'a ArenaTree<T>,
+    queue: VecDeque<(usize, usize)>,
+}
+
+impl<'a, T: Ord> Iterator for Bfs<'a, T> {
+    // depth of the node, the root has 0
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, index) = self.queue.pop_front()?;
+        let node = self.tree.node(index);
+        for child in [node.left, node.right].into_iter().flatten() {
+            self.queue.push_back((depth + 1, child));
+        }
+
+        Some((depth, &node.value))
+    }
+}
+
+// whole levels, the first one holds only the root
+pub struct Levels<'a, T: Ord> {
+    tree: &'a ArenaTree<T>,
+    level: Vec<usize>,
+}
+
+impl<'a, T: Ord> Iterator for Levels<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.level.is_empty() {
+            return None;
+        }
+
+        let tree = self.tree;
+        let next_level = self
+            .level
+            .iter()
+            .flat_map(|index| [tree.node(*index).left, tree.node(*index).right])
+            .flatten()
+            .collect();
+        let level = std::mem::replace(&mut self.level, next_level);
+
+        Some(
+            level
+                .into_iter()
+                .map(|index| &tree.node(index).value)
+                .collect(),
+        )
+    }
+}
+
+// in-order, takes the values out of the tree
+pub struct IntoIter<T> {
+    nodes: Vec<Option<ArenaNode<T>>>,
+    stack: Vec<usize>,
+}
+
+impl<T> IntoIter<T> {
+    fn push_left(&mut self, mut index: Option<usize>) {
+        while let Some(current) = index {
+            self.stack.push(current);
+            index = self.nodes[current].as_ref().unwrap().left;
+        }
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.nodes[self.stack.pop()?].take().unwrap();
+        self.push_left(node.right);
+
+        Some(node.value)
+    }
+}
+
+impl<T: Ord> IntoIterator for ArenaTree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        let mut iter = IntoIter {
+            nodes: self.nodes,
+            stack: Vec::new(),
+        };
+        iter.push_left(self.root);
+        iter
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a ArenaTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for ArenaTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = ArenaTree::empty();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for ArenaTree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for ArenaTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::tree::Tree;
+
+    // random insertions and removals compared against BTreeSet, removed places
+    // are reused
+    #[test]
+    fn arena_agrees_with_btreeset() {
+        let mut rng = thread_rng();
+        let mut tree = ArenaTree::empty();
+        let mut expected = BTreeSet::new();
+        for _ in 0..10_000 {
+            let value = rng.gen::<i8>();
+            match rng.gen_range(0..8) {
+                0..=3 => {
+                    tree.add(value);
+                    expected.insert(value);
+                }
+                4..=5 => assert_eq!(tree.remove(&value), expected.take(&value)),
+                6 => assert_eq!(tree.pop_min(), expected.pop_first()),
+                _ => assert_eq!(tree.pop_max(), expected.pop_last()),
+            }
+            assert!(tree.is_valid());
+            assert_eq!(tree.len(), expected.len());
+            assert_eq!(tree.contains(&value), expected.contains(&value));
+        }
+
+        assert!(tree.iter().eq(expected.iter()));
+        assert_eq!(tree.first(), expected.first());
+        assert_eq!(tree.last(), expected.last());
+    }
+
+    // lookups, order statistics, ranges and splits compared against BTreeSet
+    #[test]
+    fn queries_agree_with_btreeset() {
+        let mut rng = thread_rng();
+        let mut tree: ArenaTree<i16> = (0..1_000).map(|_| rng.gen_range(-2_000..2_000)).collect();
+        // leaves free places in the Vec
+        for _ in 0..200 {
+            let value = *tree.select(rng.gen_range(0..tree.len())).unwrap();
+            tree.remove(&value);
+        }
+        let mut values: BTreeSet<_> = tree.iter().copied().collect();
+        assert!(tree.is_valid());
+
+        let sorted: Vec<i16> = values.iter().copied().collect();
+        for (index, value) in sorted.iter().enumerate() {
+            assert_eq!(tree.select(index), Some(value));
+        }
+        assert_eq!(tree.select(sorted.len()), None);
+        for value in -2_100..2_100 {
+            assert_eq!(tree.floor(&value), values.range(..=value).next_back());
+            assert_eq!(tree.ceiling(&value), values.range(value..).next());
+            assert_eq!(tree.rank(&value), sorted.partition_point(|x| *x < value));
+        }
+        for _ in 0..100 {
+            let (a, b) = (rng.gen_range(-2_100..2_100), rng.gen_range(-2_100..2_100));
+            let (start, end) = (a.min(b), a.max(b));
+            assert!(tree.range(start..end).eq(values.range(start..end)));
+            assert!(tree.range(start..=end).eq(values.range(start..=end)));
+            assert!(tree.range(..end).eq(values.range(..end)));
+            assert!(tree.range(start..).eq(values.range(start..)));
+        }
+
+        for _ in 0..10 {
+            let key = rng.gen_range(-2_100..2_100);
+            let greater = tree.split_off(&key);
+            let expected_greater = values.split_off(&key);
+            assert!(tree.is_valid() && greater.is_valid());
+            assert!(tree.iter().eq(values.iter()));
+            assert!(greater.iter().eq(expected_greater.iter()));
+            assert!(greater.height() <= 1 + greater.len().max(1).ilog2() as usize);
+        }
+    }
+
+    // the same insertions give both trees the same shape
+    #[test]
+    fn traversals_agree_with_tree() {
+        let mut rng = thread_rng();
+        let values: Vec<u8> = (0..300).map(|_| rng.gen()).collect();
+        let tree: Tree<_> = values.iter().copied().collect();
+        let arena: ArenaTree<_> = values.iter().copied().collect();
+
+        assert!(arena.preorder().eq(tree.preorder()));
+        assert!(arena.postorder().eq(tree.postorder()));
+        assert!(arena.bfs().eq(tree.bfs()));
+        assert!(arena.bfs_with_depth().eq(tree.bfs_with_depth()));
+        assert!(arena.levels().eq(tree.levels()));
+        assert_eq!(arena.height(), tree.height());
+        assert!(arena.into_iter().eq(tree));
+    }
+}
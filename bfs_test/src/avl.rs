@@ -0,0 +1,205 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+
+// Self-balancing variant of Tree. Heights of the subtrees of every node differ
+// by at most one, so the height stays O(log n) even for sorted input.
+#[derive(Debug)]
+pub struct AvlTree<T: Ord> {
+    root: Option<Box<AvlNode<T>>>,
+}
+
+#[derive(Debug)]
+struct AvlNode<T: Ord> {
+    value: T,
+    // of the subtree, a leaf has 1
+    height: usize,
+    left: Option<Box<AvlNode<T>>>,
+    right: Option<Box<AvlNode<T>>>,
+}
+
+impl<T: Ord> AvlTree<T> {
+    pub fn from(value: T) -> AvlTree<T> {
+        let mut tree = AvlTree::empty();
+        tree.add(value);
+        tree
+    }
+
+    pub fn empty() -> AvlTree<T> {
+        AvlTree { root: None }
+    }
+
+    pub fn add(&mut self, value: T) {
+        self.root = Some(insert(self.root.take(), value));
+    }
+
+    pub fn contains<Q: ?Sized + Ord>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        let mut node = &self.root;
+        while let Some(current) = node {
+            node = match value.cmp(current.value.borrow()) {
+                Ordering::Equal => return true,
+                Ordering::Less => &current.left,
+                Ordering::Greater => &current.right,
+            };
+        }
+
+        false
+    }
+
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    // values are in order, stored heights are right and every node is balanced
+    pub fn is_valid(&self) -> bool {
+        check(&self.root, None, None).is_some()
+    }
+}
+
+fn height<T: Ord>(node: &Option<Box<AvlNode<T>>>) -> usize {
+    node.as_ref().map_or(0, |node| node.height)
+}
+
+// left height minus right height
+fn balance<T: Ord>(node: &AvlNode<T>) -> isize {
+    height(&node.left) as isize - height(&node.right) as isize
+}
+
+fn update_height<T: Ord>(node: &mut AvlNode<T>) {
+    node.height = 1 + height(&node.left).max(height(&node.right));
+}
+
+//     node           left
+//    /    \         /    \
+//  left    c  ->   a     node
+//  /  \                 /    \
+// a    b               b      c
+fn rotate_right<T: Ord>(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+    let mut left = node.left.take().unwrap();
+    node.left = left.right.take();
+    update_height(&mut node);
+    left.right = Some(node);
+    update_height(&mut left);
+    left
+}
+
+fn rotate_left<T: Ord>(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+    let mut right = node.right.take().unwrap();
+    node.right = right.left.take();
+    update_height(&mut node);
+    right.left = Some(node);
+    update_height(&mut right);
+    right
+}
+
+fn rebalance<T: Ord>(mut node: Box<AvlNode<T>>) -> Box<AvlNode<T>> {
+    update_height(&mut node);
+    match balance(&node) {
+        2.. => {
+            if balance(node.left.as_ref().unwrap()) < 0 {
+                node.left = Some(rotate_left(node.left.take().unwrap()));
+            }
+            rotate_right(node)
+        }
+        ..=-2 => {
+            if balance(node.right.as_ref().unwrap()) > 0 {
+                node.right = Some(rotate_right(node.right.take().unwrap()));
+            }
+            rotate_left(node)
+        }
+        _ => node,
+    }
+}
+
+fn insert<T: Ord>(node: Option<Box<AvlNode<T>>>, value: T) -> Box<AvlNode<T>> {
+    let mut node = match node {
+        None => {
+            return Box::new(AvlNode {
+                value,
+                height: 1,
+                left: None,
+                right: None,
+            })
+        }
+        Some(node) => node,
+    };
+
+    match value.cmp(&node.value) {
+        Ordering::Equal => return node,
+        Ordering::Less => node.left = Some(insert(node.left.take(), value)),
+        Ordering::Greater => node.right = Some(insert(node.right.take(), value)),
+    }
+
+    rebalance(node)
+}
+
+// height of a valid subtree with values strictly between the bounds
+fn check<T: Ord>(
+    node: &Option<Box<AvlNode<T>>>,
+    min: Option<&T>,
+    max: Option<&T>,
+) -> Option<usize> {
+    let node = match node {
+        None => return Some(0),
+        Some(node) => node,
+    };
+
+    if min.is_some_and(|min| node.value <= *min) || max.is_some_and(|max| node.value >= *max) {
+        return None;
+    }
+
+    let left = check(&node.left, min, Some(&node.value))?;
+    let right = check(&node.right, Some(&node.value), max)?;
+    let height = 1 + left.max(right);
+    if left.abs_diff(right) > 1 || height != node.height {
+        return None;
+    }
+
+    Some(height)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::tree::Tree;
+
+    // the AVL bound, a little below 1.44 * log2(n + 2)
+    fn max_height(len: usize) -> usize {
+        (1.44 * ((len + 2) as f64).log2()) as usize
+    }
+
+    // the plain tree degrades into a list on sorted input, the AVL tree does not
+    #[test]
+    fn sorted_input_stays_balanced() {
+        let mut plain = Tree::empty();
+        let mut balanced = AvlTree::empty();
+        for value in 0..1_000 {
+            plain.add(value);
+            balanced.add(value);
+            assert!(balanced.is_valid());
+        }
+
+        assert_eq!(plain.height(), 1_000);
+        assert!(balanced.height() <= max_height(1_000));
+    }
+
+    #[test]
+    fn random_input_agrees_with_the_plain_tree() {
+        let mut rng = thread_rng();
+        let values: Vec<i32> = (0..10_000).map(|_| rng.gen_range(0..20_000)).collect();
+        let plain: Tree<_> = values.iter().copied().collect();
+        let mut balanced = AvlTree::empty();
+        for &value in &values {
+            balanced.add(value);
+        }
+
+        assert!(balanced.is_valid());
+        assert!(balanced.height() <= max_height(plain.len()));
+        assert!(balanced.height() <= plain.height());
+        assert!((0..20_000).all(|value| plain.contains(&value) == balanced.contains(&value)));
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+
+use crate::node::TreeNode;
+use crate::tree::Tree;
+
+// Level-order traversal, the root first, then its children from the left, ...
+
+pub struct Bfs<'a, T: Ord> {
+    queue: VecDeque<(usize, &'a TreeNode<T, ()>)>,
+}
+
+impl<'a, T: Ord> Iterator for Bfs<'a, T> {
+    // depth of the node, the root has 0
+    type Item = (usize, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, node) = self.queue.pop_front()?;
+        for child in [&node.left, &node.right].into_iter().flatten() {
+            self.queue.push_back((depth + 1, child));
+        }
+
+        Some((depth, &node.key))
+    }
+}
+
+// whole levels, the first one holds only the root
+pub struct Levels<'a, T: Ord> {
+    level: Vec<&'a TreeNode<T, ()>>,
+}
+
+impl<'a, T: Ord> Iterator for Levels<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.level.is_empty() {
+            return None;
+        }
+
+        let next_level = self
+            .level
+            .iter()
+            .flat_map(|node| [&node.left, &node.right])
+            .flatten()
+            .map(|child| child.as_ref())
+            .collect();
+        let level = std::mem::replace(&mut self.level, next_level);
+
+        Some(level.into_iter().map(|node| &node.key).collect())
+    }
+}
+
+impl<T: Ord> Tree<T> {
+    pub fn bfs(&self) -> impl Iterator<Item = &T> {
+        self.bfs_with_depth().map(|(_, value)| value)
+    }
+
+    pub fn bfs_with_depth(&self) -> Bfs<'_, T> {
+        Bfs {
+            queue: self.root.iter().map(|root| (0, root.as_ref())).collect(),
+        }
+    }
+
+    pub fn levels(&self) -> Levels<'_, T> {
+        Levels {
+            level: self.root.iter().map(|root| root.as_ref()).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //         50
+    //     30      70
+    //   20  40  60  80
+    //      35
+    fn sample() -> Tree<i32> {
+        [50, 30, 70, 20, 40, 60, 80, 35].into_iter().collect()
+    }
+
+    #[test]
+    fn bfs_goes_level_by_level_from_the_left() {
+        let tree = sample();
+        assert!(tree.bfs().eq(&[50, 30, 70, 20, 40, 60, 80, 35]));
+        assert!(tree.bfs_with_depth().eq([
+            (0, &50),
+            (1, &30),
+            (1, &70),
+            (2, &20),
+            (2, &40),
+            (2, &60),
+            (2, &80),
+            (3, &35),
+        ]));
+    }
+
+    #[test]
+    fn levels_hold_whole_levels() {
+        let tree = sample();
+        let levels: Vec<Vec<&i32>> = tree.levels().collect();
+        assert_eq!(
+            levels,
+            [
+                vec![&50],
+                vec![&30, &70],
+                vec![&20, &40, &60, &80],
+                vec![&35],
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_tree_has_no_levels() {
+        let tree: Tree<i32> = Tree::empty();
+        assert_eq!(tree.bfs().next(), None);
+        assert_eq!(tree.bfs_with_depth().next(), None);
+        assert_eq!(tree.levels().next(), None);
+        assert_eq!(tree.height(), 0);
+    }
+}
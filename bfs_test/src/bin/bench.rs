@@ -0,0 +1,78 @@
+use std::collections::BTreeSet;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rand::prelude::*;
+
+use bfs_test::arena::ArenaTree;
+use bfs_test::tree::Tree;
+
+// Compares the boxed Tree, the arena-backed ArenaTree and BTreeSet,
+// run it with `cargo run --release --bin bench`.
+
+const VALUES: usize = 200_000;
+const LOOKUPS: usize = 200_000;
+
+fn time<R>(f: impl FnOnce() -> R) -> (R, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+fn report(name: &str, insert: Duration, lookup: Duration, iterate: Duration) {
+    println!(
+        "{:<10} {:>12.2?} {:>12.2?} {:>12.2?}",
+        name, insert, lookup, iterate
+    );
+}
+
+fn main() {
+    let mut rng = thread_rng();
+    // random order keeps the unbalanced trees logarithmic on average
+    let values: Vec<u32> = (0..VALUES).map(|_| rng.gen()).collect();
+    let lookups: Vec<u32> = (0..LOOKUPS)
+        .map(|_| {
+            if rng.gen_bool(0.5) {
+                values[rng.gen_range(0..VALUES)]
+            } else {
+                rng.gen()
+            }
+        })
+        .collect();
+
+    println!("{} random u32 values, {} lookups", VALUES, LOOKUPS);
+    println!(
+        "{:<10} {:>12} {:>12} {:>12}",
+        "", "insert", "lookup", "iterate"
+    );
+
+    let (tree, insert) = time(|| values.iter().copied().collect::<Tree<_>>());
+    let (found, lookup) = time(|| lookups.iter().filter(|value| tree.contains(value)).count());
+    let (sum, iterate) = time(|| tree.iter().map(|value| *value as u64).sum::<u64>());
+    report("Tree", insert, lookup, iterate);
+    black_box((found, sum));
+
+    let (arena, insert) = time(|| {
+        let mut arena = ArenaTree::with_capacity(VALUES);
+        arena.extend(values.iter().copied());
+        arena
+    });
+    let (found, lookup) = time(|| lookups.iter().filter(|value| arena.contains(value)).count());
+    let (sum, iterate) = time(|| arena.iter().map(|value| *value as u64).sum::<u64>());
+    report("ArenaTree", insert, lookup, iterate);
+    black_box((found, sum));
+
+    // one insert at a time like the trees, collect() would sort the values
+    // and build the set in bulk
+    let (set, insert) = time(|| {
+        let mut set = BTreeSet::new();
+        for &value in &values {
+            set.insert(value);
+        }
+        set
+    });
+    let (found, lookup) = time(|| lookups.iter().filter(|value| set.contains(value)).count());
+    let (sum, iterate) = time(|| set.iter().map(|value| *value as u64).sum::<u64>());
+    report("BTreeSet", insert, lookup, iterate);
+    black_box((found, sum));
+}
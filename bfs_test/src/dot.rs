@@ -0,0 +1,177 @@
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use std::fmt::{self, Write};
+
+use crate::tree::Tree;
+
+// Graphviz export, e.g. `dot -Tpng tree.dot -o tree.png`
+
+impl<T: Ord> Tree<T> {
+    // values the lookup of the given one compares against, from the root
+    pub fn search_path<Q: ?Sized + Ord>(&self, value: &Q) -> Vec<&T>
+    where
+        T: Borrow<Q>,
+    {
+        let mut path = Vec::new();
+        let mut node = &self.root;
+        while let Some(current) = node {
+            path.push(&current.key);
+            node = match value.cmp(current.key.borrow()) {
+                std::cmp::Ordering::Equal => break,
+                std::cmp::Ordering::Less => &current.left,
+                std::cmp::Ordering::Greater => &current.right,
+            };
+        }
+
+        path
+    }
+}
+
+impl<T: Ord + fmt::Display> Tree<T> {
+    pub fn to_dot(&self) -> String {
+        self.to_dot_highlighted([])
+    }
+
+    // highlighted values are filled, so are the edges between them,
+    // e.g. tree.to_dot_highlighted(tree.search_path(&5))
+    pub fn to_dot_highlighted<'a, I>(&'a self, highlight: I) -> String
+    where
+        I: IntoIterator<Item = &'a T>,
+    {
+        let highlight: BTreeSet<&T> = highlight.into_iter().collect();
+        let mut dot = String::from("digraph Tree {\n    node [shape=circle];\n");
+
+        // the nodes are numbered as they are reached
+        let mut next_id = 0;
+        let mut placeholders = 0;
+        let mut stack = Vec::new();
+        if let Some(root) = &self.root {
+            stack.push((root.as_ref(), next_id));
+            next_id += 1;
+        }
+        while let Some((node, id)) = stack.pop() {
+            let highlighted = highlight.contains(&node.key);
+            let label = node
+                .key
+                .to_string()
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"");
+            if highlighted {
+                writeln!(
+                    dot,
+                    "    n{} [label=\"{}\", style=filled, fillcolor=gold];",
+                    id, label
+                )
+                .unwrap();
+            } else {
+                writeln!(dot, "    n{} [label=\"{}\"];", id, label).unwrap();
+            }
+
+            // Graphviz places the children in the order of the edges, a missing
+            // child gets an invisible node so that the other one keeps its side
+            let only_child = node.left.is_some() != node.right.is_some();
+            let mut children = Vec::new();
+            for child in [&node.left, &node.right] {
+                match child {
+                    Some(child) => {
+                        let edge = if highlighted && highlight.contains(&child.key) {
+                            " [color=gold, penwidth=2]"
+                        } else {
+                            ""
+                        };
+                        writeln!(dot, "    n{} -> n{}{};", id, next_id, edge).unwrap();
+                        children.push((child.as_ref(), next_id));
+                        next_id += 1;
+                    }
+                    None if only_child => {
+                        writeln!(dot, "    p{} [label=\"\", style=invis];", placeholders).unwrap();
+                        writeln!(dot, "    n{} -> p{} [style=invis];", id, placeholders).unwrap();
+                        placeholders += 1;
+                    }
+                    None => (),
+                }
+            }
+            // the left child is numbered and printed first
+            stack.extend(children.into_iter().rev());
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //   5
+    // 3   8
+    //       9
+    fn sample() -> Tree<i32> {
+        [5, 3, 8, 9].into_iter().collect()
+    }
+
+    #[test]
+    fn search_path_ends_where_the_lookup_stops() {
+        let tree = sample();
+        assert_eq!(tree.search_path(&9), [&5, &8, &9]);
+        assert_eq!(tree.search_path(&7), [&5, &8]);
+        assert_eq!(tree.search_path(&5), [&5]);
+        assert!(Tree::<i32>::empty().search_path(&5).is_empty());
+    }
+
+    #[test]
+    fn single_children_keep_their_side() {
+        assert_eq!(
+            sample().to_dot(),
+            concat!(
+                "digraph Tree {\n",
+                "    node [shape=circle];\n",
+                "    n0 [label=\"5\"];\n",
+                "    n0 -> n1;\n",
+                "    n0 -> n2;\n",
+                "    n1 [label=\"3\"];\n",
+                "    n2 [label=\"8\"];\n",
+                "    p0 [label=\"\", style=invis];\n",
+                "    n2 -> p0 [style=invis];\n",
+                "    n2 -> n3;\n",
+                "    n3 [label=\"9\"];\n",
+                "}\n",
+            )
+        );
+        assert_eq!(
+            Tree::<i32>::empty().to_dot(),
+            "digraph Tree {\n    node [shape=circle];\n}\n"
+        );
+    }
+
+    #[test]
+    fn highlighted_path_fills_the_nodes_and_edges() {
+        let tree = sample();
+        assert_eq!(
+            tree.to_dot_highlighted(tree.search_path(&9)),
+            concat!(
+                "digraph Tree {\n",
+                "    node [shape=circle];\n",
+                "    n0 [label=\"5\", style=filled, fillcolor=gold];\n",
+                "    n0 -> n1;\n",
+                "    n0 -> n2 [color=gold, penwidth=2];\n",
+                "    n1 [label=\"3\"];\n",
+                "    n2 [label=\"8\", style=filled, fillcolor=gold];\n",
+                "    p0 [label=\"\", style=invis];\n",
+                "    n2 -> p0 [style=invis];\n",
+                "    n2 -> n3 [color=gold, penwidth=2];\n",
+                "    n3 [label=\"9\", style=filled, fillcolor=gold];\n",
+                "}\n",
+            )
+        );
+    }
+
+    #[test]
+    fn labels_are_escaped() {
+        let tree = Tree::from(String::from(r#"say "hi" \o/"#));
+        assert!(tree
+            .to_dot()
+            .contains(r#"    n0 [label="say \"hi\" \\o/"];"#));
+    }
+}
@@ -0,0 +1,338 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::node::{self, Link, TreeNode};
+
+// Set of closed intervals [start, end] on the shared tree nodes, ordered by
+// start and then by end. The value of every node is the largest end in its
+// subtree, which lets the queries skip subtrees that end too early.
+pub struct IntervalTree<T: Ord + Clone> {
+    root: Link<(T, T), T>,
+}
+
+// nodes taken out of the tree on the way down and whether the way went to
+// their left child
+type Path<T> = Vec<(Box<TreeNode<(T, T), T>>, bool)>;
+
+impl<T: Ord + Clone> IntervalTree<T> {
+    pub fn empty() -> IntervalTree<T> {
+        IntervalTree { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        node::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // false when the interval was already there, or when it starts after it
+    // ends; such an interval holds no points and is not stored
+    pub fn insert(&mut self, start: T, end: T) -> bool {
+        let key = (start, end);
+        if key.0 > key.1 || node::find(&self.root, &key).is_some() {
+            return false;
+        }
+
+        // the new end counts for every subtree on the way down
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            if node.value < key.1 {
+                node.value = key.1.clone();
+            }
+            link = if key < node.key {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+
+        let end = key.1.clone();
+        node::insert(&mut self.root, key, end);
+        true
+    }
+
+    pub fn contains(&self, start: &T, end: &T) -> bool {
+        self.find(start, end).is_some()
+    }
+
+    // false when there was no such interval
+    pub fn remove(&mut self, start: &T, end: &T) -> bool {
+        if self.find(start, end).is_none() {
+            return false;
+        }
+
+        // the path is taken apart and put back together from the bottom,
+        // so that the sizes and ends are recomputed on the way up
+        let mut path = Vec::new();
+        let mut link = self.root.take();
+        let mut removed = loop {
+            let mut node = link.unwrap();
+            let left = match (start, end).cmp(&(&node.key.0, &node.key.1)) {
+                Ordering::Equal => break node,
+                Ordering::Less => true,
+                Ordering::Greater => false,
+            };
+            link = if left {
+                node.left.take()
+            } else {
+                node.right.take()
+            };
+            path.push((node, left));
+        };
+
+        // the in-order successor takes the place of a node with both children
+        let replacement = match (removed.left.take(), removed.right.take()) {
+            (None, child) | (child, None) => child,
+            (left, Some(right)) => {
+                let mut successors = Vec::new();
+                let mut successor = right;
+                while let Some(smaller) = successor.left.take() {
+                    successors.push((successor, true));
+                    successor = smaller;
+                }
+                let right = successor.right.take();
+                successor.right = reattach(successors, right);
+                successor.left = left;
+                update(&mut successor);
+                Some(successor)
+            }
+        };
+
+        self.root = reattach(path, replacement);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        node::drop_nodes(self.root.take());
+    }
+
+    // all intervals, by start and then by end
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &T)> {
+        node::InOrder::new(&self.root).map(|node| (&node.key.0, &node.key.1))
+    }
+
+    // intervals that contain the point
+    pub fn stabbing(&self, point: &T) -> Overlapping<'_, T> {
+        self.overlapping(point, point)
+    }
+
+    // intervals that share at least one point with [start, end],
+    // by start and then by end
+    pub fn overlapping(&self, start: &T, end: &T) -> Overlapping<'_, T> {
+        let mut iter = Overlapping {
+            stack: Vec::new(),
+            start: start.clone(),
+            end: end.clone(),
+        };
+        iter.push_left(&self.root);
+        iter
+    }
+
+    // checks the order, the sizes and the largest ends
+    pub fn is_valid(&self) -> bool {
+        self.iter().zip(self.iter().skip(1)).all(|(a, b)| a < b)
+            && node::InOrder::new(&self.root).all(|node| {
+                node.key.0 <= node.key.1
+                    && node.size == 1 + node::size(&node.left) + node::size(&node.right)
+                    && node.value == max_end(node)
+            })
+    }
+
+    fn find(&self, start: &T, end: &T) -> Option<&TreeNode<(T, T), T>> {
+        let mut link = &self.root;
+        while let Some(node) = link {
+            link = match (start, end).cmp(&(&node.key.0, &node.key.1)) {
+                Ordering::Equal => return Some(node),
+                Ordering::Less => &node.left,
+                Ordering::Greater => &node.right,
+            };
+        }
+
+        None
+    }
+}
+
+// largest end of the node and its children
+fn max_end<T: Ord + Clone>(node: &TreeNode<(T, T), T>) -> T {
+    [&node.left, &node.right]
+        .into_iter()
+        .flatten()
+        .map(|child| &child.value)
+        .fold(&node.key.1, |max, end| max.max(end))
+        .clone()
+}
+
+fn update<T: Ord + Clone>(node: &mut TreeNode<(T, T), T>) {
+    node.size = 1 + node::size(&node.left) + node::size(&node.right);
+    node.value = max_end(node);
+}
+
+// puts the nodes of the path back, the last one gets the given child
+// in place of the one that was taken
+fn reattach<T: Ord + Clone>(mut path: Path<T>, mut child: Link<(T, T), T>) -> Link<(T, T), T> {
+    while let Some((mut node, left)) = path.pop() {
+        if left {
+            node.left = child;
+        } else {
+            node.right = child;
+        }
+        update(&mut node);
+        child = Some(node);
+    }
+
+    child
+}
+
+pub struct Overlapping<'a, T: Ord + Clone> {
+    stack: Vec<&'a TreeNode<(T, T), T>>,
+    start: T,
+    end: T,
+}
+
+impl<'a, T: Ord + Clone> Overlapping<'a, T> {
+    // subtrees whose largest end is before the start have nothing to give
+    fn push_left(&mut self, mut link: &'a Link<(T, T), T>) {
+        while let Some(node) = link {
+            if node.value < self.start {
+                break;
+            }
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, T: Ord + Clone> Iterator for Overlapping<'a, T> {
+    type Item = (&'a T, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.stack.pop()?;
+            // everything after it starts after the end too
+            if node.key.0 > self.end {
+                self.stack.clear();
+                return None;
+            }
+
+            self.push_left(&node.right);
+            if node.key.1 >= self.start {
+                return Some((&node.key.0, &node.key.1));
+            }
+        }
+    }
+}
+
+impl<T: Ord + Clone> Drop for IntervalTree<T> {
+    fn drop(&mut self) {
+        node::drop_nodes(self.root.take());
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<(T, T)> for IntervalTree<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut tree = IntervalTree::empty();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord + Clone> Extend<(T, T)> for IntervalTree<T> {
+    fn extend<I: IntoIterator<Item = (T, T)>>(&mut self, iter: I) {
+        for (start, end) in iter {
+            self.insert(start, end);
+        }
+    }
+}
+
+impl<T: Ord + Clone + fmt::Debug> fmt::Debug for IntervalTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set()
+            .entries(self.iter().map(|(start, end)| start..=end))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::thread;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    // queries compared against filtering all the intervals, the largest ends
+    // are checked after every change
+    #[test]
+    fn queries_agree_with_a_linear_scan() {
+        let mut rng = thread_rng();
+        let mut tree = IntervalTree::empty();
+        let mut expected = BTreeSet::new();
+        for _ in 0..3_000 {
+            let start = rng.gen_range(0..1_000);
+            let end = start + rng.gen_range(0..50);
+            if rng.gen_bool(0.6) {
+                assert_eq!(tree.insert(start, end), expected.insert((start, end)));
+            } else {
+                assert_eq!(tree.remove(&start, &end), expected.remove(&(start, end)));
+            }
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.is_valid());
+
+            let point = rng.gen_range(-10..1_060);
+            assert!(tree.stabbing(&point).eq(expected
+                .iter()
+                .filter(|(start, end)| *start <= point && point <= *end)
+                .map(|(start, end)| (start, end))));
+
+            let (low, high) = (start - 20, end + rng.gen_range(0..20));
+            assert!(tree.overlapping(&low, &high).eq(expected
+                .iter()
+                .filter(|(start, end)| *start <= high && low <= *end)
+                .map(|(start, end)| (start, end))));
+        }
+
+        assert!(tree
+            .iter()
+            .eq(expected.iter().map(|(start, end)| (start, end))));
+    }
+
+    #[test]
+    fn reversed_intervals_are_not_inserted() {
+        let mut tree = IntervalTree::empty();
+        assert!(tree.insert(3, 5));
+        assert!(!tree.insert(5, 3));
+        assert!(tree.insert(4, 4));
+        assert_eq!(tree.len(), 2);
+        assert!(!tree.contains(&5, &3));
+        assert!(tree.is_valid());
+
+        tree.extend([(9, 1), (6, 8)]);
+        assert!(tree.iter().eq([(&3, &5), (&4, &4), (&6, &8)]));
+    }
+
+    // sorted input makes the tree a list, the stack is small so that any
+    // recursion over the whole depth overflows it
+    #[test]
+    fn deep_tree_without_recursion() {
+        thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(deep_tree)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    fn deep_tree() {
+        // insertion into a list is linear, the whole run is quadratic
+        const LENGTH: u32 = 5_000;
+
+        let mut tree: IntervalTree<_> = (0..LENGTH).map(|start| (start, start + 10)).collect();
+        assert_eq!(tree.stabbing(&LENGTH).count(), 10);
+        assert!(tree.remove(&0, &10));
+        assert!(tree.is_valid());
+        drop(tree);
+    }
+}
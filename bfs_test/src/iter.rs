@@ -0,0 +1,189 @@
+use std::borrow::Borrow;
+use std::ops::RangeBounds;
+
+use crate::node;
+use crate::tree::Tree;
+
+// Depth-first traversals of Tree. They keep the path in a stack instead of recursing.
+
+// in-order, smallest value first
+pub struct Iter<'a, T: Ord> {
+    nodes: node::InOrder<'a, T, ()>,
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.key)
+    }
+}
+
+// in-order within the bounds
+pub struct Range<'a, T: Ord, Q: ?Sized, R> {
+    nodes: node::Range<'a, T, (), Q, R>,
+}
+
+impl<'a, T, Q, R> Iterator for Range<'a, T, Q, R>
+where
+    T: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.key)
+    }
+}
+
+// node before its subtrees
+pub struct Preorder<'a, T: Ord> {
+    nodes: node::Preorder<'a, T, ()>,
+}
+
+impl<'a, T: Ord> Iterator for Preorder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.key)
+    }
+}
+
+// subtrees before their node
+pub struct Postorder<'a, T: Ord> {
+    nodes: node::Postorder<'a, T, ()>,
+}
+
+impl<'a, T: Ord> Iterator for Postorder<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.key)
+    }
+}
+
+// in-order, takes the values out of the tree
+pub struct IntoIter<T: Ord> {
+    nodes: node::IntoIter<T, ()>,
+}
+
+impl<T: Ord> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|(value, _)| value)
+    }
+}
+
+impl<T: Ord> Tree<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            nodes: node::InOrder::new(&self.root),
+        }
+    }
+
+    // e.g. tree.range(10..20) or tree.range::<str, _>((Bound::Excluded("a"), Bound::Unbounded))
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, T, Q, R>
+    where
+        T: Borrow<Q>,
+        Q: ?Sized + Ord,
+        R: RangeBounds<Q>,
+    {
+        Range {
+            nodes: node::Range::new(&self.root, range),
+        }
+    }
+
+    pub fn preorder(&self) -> Preorder<'_, T> {
+        Preorder {
+            nodes: node::Preorder::new(&self.root),
+        }
+    }
+
+    pub fn postorder(&self) -> Postorder<'_, T> {
+        Postorder {
+            nodes: node::Postorder::new(&self.root),
+        }
+    }
+}
+
+impl<T: Ord> IntoIterator for Tree<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter {
+            nodes: node::IntoIter::new(self.root.take()),
+        }
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a Tree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Tree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Tree::empty();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<T: Ord> Extend<T> for Tree<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //         50
+    //     30      70
+    //   20  40  60  80
+    //      35
+    fn sample() -> Tree<i32> {
+        [50, 30, 70, 20, 40, 60, 80, 35].into_iter().collect()
+    }
+
+    #[test]
+    fn depth_first_orders() {
+        let tree = sample();
+        assert!(tree.iter().eq(&[20, 30, 35, 40, 50, 60, 70, 80]));
+        assert!(tree.preorder().eq(&[50, 30, 20, 40, 35, 70, 60, 80]));
+        assert!(tree.postorder().eq(&[20, 35, 40, 30, 60, 80, 70, 50]));
+        assert!(tree.range(35..=60).eq(&[35, 40, 50, 60]));
+    }
+
+    #[test]
+    fn empty_tree_yields_nothing() {
+        let tree: Tree<i32> = Tree::empty();
+        assert_eq!(tree.iter().next(), None);
+        assert_eq!(tree.preorder().next(), None);
+        assert_eq!(tree.postorder().next(), None);
+        assert_eq!(tree.into_iter().next(), None);
+    }
+
+    #[test]
+    fn collecting_and_extending_skip_duplicates() {
+        let mut tree: Tree<i32> = [3, 1, 3, 2].into_iter().collect();
+        assert!(tree.preorder().eq(&[3, 1, 2]));
+
+        tree.extend([5, 1, 4]);
+        assert_eq!(tree.len(), 5);
+        let borrowed: Vec<&i32> = (&tree).into_iter().collect();
+        assert_eq!(borrowed, [&1, &2, &3, &4, &5]);
+        let owned: Vec<i32> = tree.into_iter().collect();
+        assert_eq!(owned, [1, 2, 3, 4, 5]);
+    }
+}
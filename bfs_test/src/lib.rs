@@ -0,0 +1,13 @@
+pub mod arena;
+pub mod avl;
+pub mod bfs;
+pub mod dot;
+pub mod interval;
+pub mod iter;
+pub mod map;
+pub mod multiset;
+mod node;
+pub mod persistent;
+pub mod set_ops;
+pub mod shape;
+pub mod tree;
@@ -0,0 +1,435 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use crate::node::{self, Link, TreeNode};
+
+// Key-value variant of Tree, the nodes and traversals are the same.
+pub struct TreeMap<K: Ord, V> {
+    root: Link<K, V>,
+}
+
+impl<K: Ord, V> TreeMap<K, V> {
+    pub fn empty() -> TreeMap<K, V> {
+        TreeMap { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        node::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // returns the value the key had before
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn contains_key<Q: ?Sized + Ord>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    pub fn get<Q: ?Sized + Ord>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        node::find(&self.root, key).map(|node| &node.value)
+    }
+
+    pub fn get_mut<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        node::find_slot(&mut self.root, key)
+            .as_mut()
+            .map(|node| &mut node.value)
+    }
+
+    // e.g. *map.entry(word).or_insert(0) += 1
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let (slot, sizes) = node::find_slot_with_sizes(&mut self.root, &key);
+        if slot.is_some() {
+            Entry::Occupied(OccupiedEntry { slot, sizes })
+        } else {
+            Entry::Vacant(VacantEntry { key, slot, sizes })
+        }
+    }
+
+    pub fn remove<Q: ?Sized + Ord>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        node::remove(&mut self.root, key).map(|(_, value)| value)
+    }
+
+    pub fn clear(&mut self) {
+        node::drop_nodes(self.root.take());
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            nodes: node::InOrder::new(&self.root),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            nodes: node::InOrderMut::new(&mut self.root),
+        }
+    }
+
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            nodes: node::InOrder::new(&self.root),
+        }
+    }
+
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            nodes: node::InOrder::new(&self.root),
+        }
+    }
+
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            nodes: node::InOrderMut::new(&mut self.root),
+        }
+    }
+}
+
+pub enum Entry<'a, K: Ord, V> {
+    Vacant(VacantEntry<'a, K, V>),
+    Occupied(OccupiedEntry<'a, K, V>),
+}
+
+// entries keep the slot of the key and the subtree sizes of the nodes above
+// it, which change only when a node is inserted or removed through the entry
+pub struct VacantEntry<'a, K: Ord, V> {
+    key: K,
+    // empty
+    slot: &'a mut Link<K, V>,
+    sizes: Vec<&'a mut usize>,
+}
+
+pub struct OccupiedEntry<'a, K: Ord, V> {
+    // holds the node with the key
+    slot: &'a mut Link<K, V>,
+    sizes: Vec<&'a mut usize>,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Vacant(entry) => entry.key(),
+            Entry::Occupied(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Vacant(entry) => entry.insert(default()),
+            Entry::Occupied(entry) => entry.into_mut(),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+
+        self
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        for size in self.sizes {
+            *size += 1;
+        }
+
+        &mut self
+            .slot
+            .insert(Box::new(TreeNode::new(self.key, value)))
+            .value
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    fn node(&self) -> &TreeNode<K, V> {
+        self.slot.as_ref().unwrap()
+    }
+
+    pub fn key(&self) -> &K {
+        &self.node().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.node().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.slot.as_mut().unwrap().value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.slot.as_mut().unwrap().value
+    }
+
+    // returns the old value
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        for size in self.sizes {
+            *size -= 1;
+        }
+
+        node::remove_node(self.slot).unwrap().1
+    }
+}
+
+// in-order, smallest key first
+pub struct Iter<'a, K: Ord, V> {
+    nodes: node::InOrder<'a, K, V>,
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| (&node.key, &node.value))
+    }
+}
+
+pub struct IterMut<'a, K: Ord, V> {
+    nodes: node::InOrderMut<'a, K, V>,
+}
+
+impl<'a, K: Ord, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next()
+    }
+}
+
+pub struct Keys<'a, K: Ord, V> {
+    nodes: node::InOrder<'a, K, V>,
+}
+
+impl<'a, K: Ord, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.key)
+    }
+}
+
+pub struct Values<'a, K: Ord, V> {
+    nodes: node::InOrder<'a, K, V>,
+}
+
+impl<'a, K: Ord, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|node| &node.value)
+    }
+}
+
+pub struct ValuesMut<'a, K: Ord, V> {
+    nodes: node::InOrderMut<'a, K, V>,
+}
+
+impl<'a, K: Ord, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next().map(|(_, value)| value)
+    }
+}
+
+// in-order, takes the pairs out of the map
+pub struct IntoIter<K: Ord, V> {
+    nodes: node::IntoIter<K, V>,
+}
+
+impl<K: Ord, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.nodes.next()
+    }
+}
+
+impl<K: Ord, V> IntoIterator for TreeMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        IntoIter {
+            nodes: node::IntoIter::new(self.root.take()),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a TreeMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a mut TreeMap<K, V> {
+    type Item = (&'a K, &'a mut V);
+    type IntoIter = IterMut<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for TreeMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = TreeMap::empty();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for TreeMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord, V> Drop for TreeMap<K, V> {
+    fn drop(&mut self) {
+        node::drop_nodes(self.root.take());
+    }
+}
+
+impl<K: Ord + fmt::Debug, V: fmt::Debug> fmt::Debug for TreeMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    // random map operations compared against BTreeMap
+    #[test]
+    fn map_agrees_with_btreemap() {
+        let mut rng = thread_rng();
+        let mut map = TreeMap::empty();
+        let mut expected = BTreeMap::new();
+        for _ in 0..10_000 {
+            let key = rng.gen::<i8>();
+            let value = rng.gen::<u16>() as u32;
+            match rng.gen_range(0..5) {
+                0 => assert_eq!(map.insert(key, value), expected.insert(key, value)),
+                1 => assert_eq!(map.remove(&key), expected.remove(&key)),
+                2 => {
+                    if let Some(value) = map.get_mut(&key) {
+                        *value += 1;
+                    }
+                    if let Some(value) = expected.get_mut(&key) {
+                        *value += 1;
+                    }
+                }
+                3 => {
+                    *map.entry(key).and_modify(|v| *v *= 2).or_insert(value) += 1;
+                    *expected.entry(key).and_modify(|v| *v *= 2).or_insert(value) += 1;
+                }
+                _ => assert_eq!(map.get(&key), expected.get(&key)),
+            }
+        }
+
+        assert_eq!(map.len(), expected.len());
+        assert!(map.iter().eq(expected.iter()));
+        assert!(map.keys().eq(expected.keys()));
+        for value in map.values_mut() {
+            *value /= 2;
+        }
+        for value in expected.values_mut() {
+            *value /= 2;
+        }
+        assert!(map.values().eq(expected.values()));
+        assert!(map.into_iter().eq(expected));
+
+        let mut counts: TreeMap<&str, usize> = TreeMap::empty();
+        for word in "a rose is a rose is a rose".split(' ') {
+            *counts.entry(word).or_default() += 1;
+        }
+        assert_eq!(format!("{:?}", counts), r#"{"a": 3, "is": 2, "rose": 3}"#);
+    }
+
+    fn sizes_are_valid<K: Ord, V>(map: &TreeMap<K, V>) -> bool {
+        node::InOrder::new(&map.root)
+            .all(|node| node.size == 1 + node::size(&node.left) + node::size(&node.right))
+    }
+
+    // entries change the sizes only when they insert or remove
+    #[test]
+    fn entries_keep_the_sizes() {
+        let mut map: TreeMap<_, _> = TreeMap::empty();
+        for key in [50, 20, 80, 10, 30, 70, 90] {
+            map.insert(key, key);
+        }
+
+        assert!(matches!(map.entry(40), Entry::Vacant(_)));
+        assert!(matches!(map.entry(30), Entry::Occupied(_)));
+        assert_eq!(map.len(), 7);
+        assert!(sizes_are_valid(&map));
+
+        *map.entry(40).or_insert(0) += 1;
+        assert_eq!(map.len(), 8);
+        assert!(sizes_are_valid(&map));
+
+        for (key, value) in [(20, 20), (40, 1), (50, 50)] {
+            match map.entry(key) {
+                Entry::Occupied(entry) => assert_eq!(entry.remove(), value),
+                Entry::Vacant(_) => panic!("{} is in the map", key),
+            }
+            assert!(sizes_are_valid(&map));
+        }
+        assert_eq!(map.len(), 5);
+        assert!(map.keys().eq([10, 30, 70, 80, 90].iter()));
+    }
+}
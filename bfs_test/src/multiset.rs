@@ -0,0 +1,212 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use crate::map::{self, TreeMap};
+
+// Tree that keeps equal values, every node stores how many times its value
+// was added.
+pub struct Multiset<T: Ord> {
+    counts: TreeMap<T, usize>,
+    // all values, repeated ones included
+    len: usize,
+}
+
+impl<T: Ord> Multiset<T> {
+    pub fn empty() -> Multiset<T> {
+        Multiset {
+            counts: TreeMap::empty(),
+            len: 0,
+        }
+    }
+
+    pub fn add(&mut self, value: T) {
+        self.add_many(value, 1);
+    }
+
+    pub fn add_many(&mut self, value: T, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        *self.counts.entry(value).or_insert(0) += count;
+        self.len += count;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // number of different values
+    pub fn distinct_len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn count<Q: ?Sized + Ord>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        self.counts.get(value).copied().unwrap_or(0)
+    }
+
+    pub fn contains<Q: ?Sized + Ord>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.counts.contains_key(value)
+    }
+
+    // false when there was nothing to remove
+    pub fn remove_one<Q: ?Sized + Ord>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        match self.counts.get_mut(value) {
+            None => return false,
+            Some(count) if *count > 1 => *count -= 1,
+            Some(_) => {
+                self.counts.remove(value);
+            }
+        }
+
+        self.len -= 1;
+        true
+    }
+
+    // returns how many were removed
+    pub fn remove_all<Q: ?Sized + Ord>(&mut self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        let count = self.counts.remove(value).unwrap_or(0);
+        self.len -= count;
+        count
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.len = 0;
+    }
+
+    // in order, every value as many times as it was added
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            counts: self.counts.iter(),
+            current: None,
+        }
+    }
+
+    // in order, every value once with its count
+    pub fn iter_counts(&self) -> impl Iterator<Item = (&T, usize)> {
+        self.counts.iter().map(|(value, count)| (value, *count))
+    }
+}
+
+pub struct Iter<'a, T: Ord> {
+    counts: map::Iter<'a, T, usize>,
+    // value being repeated and how many times it is still to come
+    current: Option<(&'a T, usize)>,
+}
+
+impl<'a, T: Ord> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, remaining) = match self.current {
+            Some((value, remaining)) if remaining > 0 => (value, remaining),
+            _ => {
+                let (value, count) = self.counts.next()?;
+                (value, *count)
+            }
+        };
+
+        self.current = Some((value, remaining - 1));
+        Some(value)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a Multiset<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for Multiset<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut multiset = Multiset::empty();
+        multiset.extend(iter);
+        multiset
+    }
+}
+
+impl<T: Ord> Extend<T> for Multiset<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.add(value);
+        }
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for Multiset<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    // multiset operations compared against counts in a BTreeMap
+    #[test]
+    fn counts_agree_with_btreemap() {
+        let mut rng = thread_rng();
+        let mut multiset = Multiset::empty();
+        let mut expected: BTreeMap<i8, usize> = BTreeMap::new();
+        for _ in 0..10_000 {
+            let value = rng.gen_range(-20..20);
+            match rng.gen_range(0..6) {
+                0..=2 => {
+                    multiset.add(value);
+                    *expected.entry(value).or_default() += 1;
+                }
+                3..=4 => {
+                    let removed = match expected.get_mut(&value) {
+                        Some(count) if *count > 1 => {
+                            *count -= 1;
+                            true
+                        }
+                        Some(_) => expected.remove(&value).is_some(),
+                        None => false,
+                    };
+                    assert_eq!(multiset.remove_one(&value), removed);
+                }
+                _ => assert_eq!(
+                    multiset.remove_all(&value),
+                    expected.remove(&value).unwrap_or(0)
+                ),
+            }
+            assert_eq!(
+                multiset.count(&value),
+                expected.get(&value).copied().unwrap_or(0)
+            );
+        }
+
+        assert_eq!(multiset.len(), expected.values().sum::<usize>());
+        assert_eq!(multiset.distinct_len(), expected.len());
+        let repeated = expected
+            .iter()
+            .flat_map(|(value, count)| std::iter::repeat_n(value, *count));
+        assert!(multiset.iter().eq(repeated));
+    }
+}
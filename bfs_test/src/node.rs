@@ -0,0 +1,626 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+
+// Nodes and traversals shared by Tree and TreeMap, a set stores () as the value.
+// Everything walks the tree in loops, a degenerate tree is as deep as it is
+// long and recursion would overflow the stack.
+
+pub(crate) type Link<K, V> = Option<Box<TreeNode<K, V>>>;
+
+pub(crate) struct TreeNode<K: Ord, V> {
+    pub(crate) key: K,
+    pub(crate) value: V,
+    // number of nodes in the subtree, the node included
+    pub(crate) size: usize,
+    pub(crate) left: Link<K, V>,
+    pub(crate) right: Link<K, V>,
+}
+
+impl<K: Ord, V> TreeNode<K, V> {
+    pub(crate) fn new(key: K, value: V) -> TreeNode<K, V> {
+        TreeNode {
+            key,
+            value,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+pub(crate) fn size<K: Ord, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+pub(crate) fn find<'a, K, V, Q>(mut link: &'a Link<K, V>, key: &Q) -> Option<&'a TreeNode<K, V>>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    while let Some(node) = link {
+        link = match key.cmp(node.key.borrow()) {
+            Ordering::Equal => return Some(node),
+            Ordering::Less => &node.left,
+            Ordering::Greater => &node.right,
+        };
+    }
+
+    None
+}
+
+// slot holding the node with the key, or the empty slot where it belongs
+pub(crate) fn find_slot<'a, K, V, Q>(mut slot: &'a mut Link<K, V>, key: &Q) -> &'a mut Link<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    while let Some(node) = slot {
+        match key.cmp(node.key.borrow()) {
+            Ordering::Equal => break,
+            Ordering::Less => slot = &mut slot.as_mut().unwrap().left,
+            Ordering::Greater => slot = &mut slot.as_mut().unwrap().right,
+        }
+    }
+
+    slot
+}
+
+// find_slot that also keeps the sizes of the nodes above the slot, so that
+// an insertion or removal there can update them without another search
+pub(crate) fn find_slot_with_sizes<'a, K, V, Q>(
+    mut slot: &'a mut Link<K, V>,
+    key: &Q,
+) -> (&'a mut Link<K, V>, Vec<&'a mut usize>)
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    let mut sizes = Vec::new();
+    while slot.as_ref().is_some_and(|node| key != node.key.borrow()) {
+        let TreeNode {
+            key: node_key,
+            size,
+            left,
+            right,
+            ..
+        } = &mut **slot.as_mut().unwrap();
+        slot = if key < (*node_key).borrow() {
+            left
+        } else {
+            right
+        };
+        sizes.push(size);
+    }
+
+    (slot, sizes)
+}
+
+// the key must not be in the subtree yet, every node on the way gets one
+// more descendant
+pub(crate) fn insert<K: Ord, V>(
+    mut slot: &mut Link<K, V>,
+    key: K,
+    value: V,
+) -> &mut TreeNode<K, V> {
+    while let Some(node) = slot {
+        node.size += 1;
+        slot = if key < node.key {
+            &mut node.left
+        } else {
+            &mut node.right
+        };
+    }
+
+    slot.insert(Box::new(TreeNode::new(key, value)))
+}
+
+// k-th smallest node, counted from 0
+pub(crate) fn select<K: Ord, V>(mut link: &Link<K, V>, mut k: usize) -> Option<&TreeNode<K, V>> {
+    while let Some(node) = link {
+        let left = size(&node.left);
+        link = match k.cmp(&left) {
+            Ordering::Equal => return Some(node),
+            Ordering::Less => &node.left,
+            Ordering::Greater => {
+                k -= left + 1;
+                &node.right
+            }
+        };
+    }
+
+    None
+}
+
+// number of keys less than the given one
+pub(crate) fn rank<K, V, Q>(mut link: &Link<K, V>, key: &Q) -> usize
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    let mut rank = 0;
+    while let Some(node) = link {
+        link = match key.cmp(node.key.borrow()) {
+            Ordering::Equal => return rank + size(&node.left),
+            Ordering::Less => &node.left,
+            Ordering::Greater => {
+                rank += size(&node.left) + 1;
+                &node.right
+            }
+        };
+    }
+
+    rank
+}
+
+pub(crate) fn first<K: Ord, V>(link: &Link<K, V>) -> Option<&TreeNode<K, V>> {
+    let mut node = link.as_deref()?;
+    while let Some(left) = &node.left {
+        node = left;
+    }
+
+    Some(node)
+}
+
+pub(crate) fn last<K: Ord, V>(link: &Link<K, V>) -> Option<&TreeNode<K, V>> {
+    let mut node = link.as_deref()?;
+    while let Some(right) = &node.right {
+        node = right;
+    }
+
+    Some(node)
+}
+
+// node with the largest key less than or equal to the given one
+pub(crate) fn floor<'a, K, V, Q>(mut link: &'a Link<K, V>, key: &Q) -> Option<&'a TreeNode<K, V>>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    let mut found = None;
+    while let Some(node) = link {
+        link = match key.cmp(node.key.borrow()) {
+            Ordering::Equal => return Some(node),
+            Ordering::Less => &node.left,
+            Ordering::Greater => {
+                found = Some(node.as_ref());
+                &node.right
+            }
+        };
+    }
+
+    found
+}
+
+// node with the smallest key greater than or equal to the given one
+pub(crate) fn ceiling<'a, K, V, Q>(mut link: &'a Link<K, V>, key: &Q) -> Option<&'a TreeNode<K, V>>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    let mut found = None;
+    while let Some(node) = link {
+        link = match key.cmp(node.key.borrow()) {
+            Ordering::Equal => return Some(node),
+            Ordering::Less => {
+                found = Some(node.as_ref());
+                &node.left
+            }
+            Ordering::Greater => &node.right,
+        };
+    }
+
+    found
+}
+
+pub(crate) fn remove<K, V, Q>(mut slot: &mut Link<K, V>, key: &Q) -> Option<(K, V)>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    // the sizes on the way change only when there is something to remove
+    find(slot, key)?;
+    loop {
+        match key.cmp(slot.as_ref().unwrap().key.borrow()) {
+            Ordering::Equal => break,
+            Ordering::Less => {
+                let node = slot.as_mut().unwrap();
+                node.size -= 1;
+                slot = &mut node.left;
+            }
+            Ordering::Greater => {
+                let node = slot.as_mut().unwrap();
+                node.size -= 1;
+                slot = &mut node.right;
+            }
+        }
+    }
+
+    remove_node(slot)
+}
+
+// removes the node in the slot, its place is taken by the in-order successor
+// when it has both children
+pub(crate) fn remove_node<K: Ord, V>(slot: &mut Link<K, V>) -> Option<(K, V)> {
+    let mut node = slot.take()?;
+    match (node.left.take(), node.right.take()) {
+        (None, right) => *slot = right,
+        (left, None) => *slot = left,
+        (left, mut right) => {
+            let (key, value) = pop_first(&mut right).unwrap();
+            let key = std::mem::replace(&mut node.key, key);
+            let value = std::mem::replace(&mut node.value, value);
+            node.size -= 1;
+            node.left = left;
+            node.right = right;
+            *slot = Some(node);
+            return Some((key, value));
+        }
+    }
+
+    Some((node.key, node.value))
+}
+
+pub(crate) fn pop_first<K: Ord, V>(mut slot: &mut Link<K, V>) -> Option<(K, V)> {
+    while slot.as_ref()?.left.is_some() {
+        let node = slot.as_mut().unwrap();
+        node.size -= 1;
+        slot = &mut node.left;
+    }
+
+    let mut node = slot.take()?;
+    *slot = node.right.take();
+    Some((node.key, node.value))
+}
+
+pub(crate) fn pop_last<K: Ord, V>(mut slot: &mut Link<K, V>) -> Option<(K, V)> {
+    while slot.as_ref()?.right.is_some() {
+        let node = slot.as_mut().unwrap();
+        node.size -= 1;
+        slot = &mut node.right;
+    }
+
+    let mut node = slot.take()?;
+    *slot = node.left.take();
+    Some((node.key, node.value))
+}
+
+// moves the nodes with keys greater than or equal to the given one into the
+// returned subtree, the nodes keep their relative positions so no keys are
+// compared twice
+pub(crate) fn split_off<K, V, Q>(link: &mut Link<K, V>, key: &Q) -> Link<K, V>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+{
+    let mut greater = None;
+    let mut rest = link.take();
+    let mut less_slot = &mut *link;
+    let mut greater_slot = &mut greater;
+    while let Some(mut node) = rest {
+        if node.key.borrow() < key {
+            rest = node.right.take();
+            less_slot = &mut less_slot.insert(node).right;
+        } else {
+            rest = node.left.take();
+            greater_slot = &mut greater_slot.insert(node).left;
+        }
+    }
+
+    // the moved nodes are exactly the right spine of the smaller part and the
+    // left spine of the greater one
+    fix_spine_sizes(link, false);
+    fix_spine_sizes(&mut greater, true);
+    greater
+}
+
+// recomputes the sizes along the left or right spine from the bottom up
+fn fix_spine_sizes<K: Ord, V>(link: &mut Link<K, V>, left_spine: bool) {
+    let mut other_sizes = Vec::new();
+    let mut node = link.as_deref();
+    while let Some(current) = node {
+        let (spine, other) = if left_spine {
+            (&current.left, &current.right)
+        } else {
+            (&current.right, &current.left)
+        };
+        other_sizes.push(size(other));
+        node = spine.as_deref();
+    }
+
+    let mut sizes = vec![0; other_sizes.len()];
+    let mut below = 0;
+    for (index, other) in other_sizes.iter().enumerate().rev() {
+        below += 1 + other;
+        sizes[index] = below;
+    }
+
+    let mut slot = link;
+    for size in sizes {
+        let node = slot.as_mut().unwrap();
+        node.size = size;
+        slot = if left_spine {
+            &mut node.left
+        } else {
+            &mut node.right
+        };
+    }
+}
+
+// subtree of the next len pairs, which have to be in order, with the smallest
+// possible height; the recursion is only as deep as the result
+pub(crate) fn build_balanced<K, V, I>(pairs: &mut I, len: usize) -> Link<K, V>
+where
+    K: Ord,
+    I: Iterator<Item = (K, V)>,
+{
+    if len == 0 {
+        return None;
+    }
+
+    let left = build_balanced(pairs, len / 2);
+    let (key, value) = pairs.next()?;
+    let right = build_balanced(pairs, len - len / 2 - 1);
+    Some(Box::new(TreeNode {
+        key,
+        value,
+        size: len,
+        left,
+        right,
+    }))
+}
+
+// drops the subtree node by node, the default drop recurses into the children
+pub(crate) fn drop_nodes<K: Ord, V>(link: Link<K, V>) {
+    let mut stack: Vec<_> = link.into_iter().collect();
+    while let Some(mut node) = stack.pop() {
+        stack.extend(node.left.take());
+        stack.extend(node.right.take());
+    }
+}
+
+// in-order, smallest key first
+pub(crate) struct InOrder<'a, K: Ord, V> {
+    stack: Vec<&'a TreeNode<K, V>>,
+}
+
+impl<'a, K: Ord, V> InOrder<'a, K, V> {
+    pub(crate) fn new(link: &'a Link<K, V>) -> Self {
+        let mut iter = InOrder { stack: Vec::new() };
+        iter.push_left(link.as_deref());
+        iter
+    }
+
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for InOrder<'a, K, V> {
+    type Item = &'a TreeNode<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+
+        Some(node)
+    }
+}
+
+// in-order within the bounds, subtrees outside of them are never entered
+pub(crate) struct Range<'a, K: Ord, V, Q: ?Sized, R> {
+    stack: Vec<&'a TreeNode<K, V>>,
+    range: R,
+    key: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K, V, Q, R> Range<'a, K, V, Q, R>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    pub(crate) fn new(link: &'a Link<K, V>, range: R) -> Self {
+        let mut iter = Range {
+            stack: Vec::new(),
+            range,
+            key: PhantomData,
+        };
+        iter.push_left(link.as_deref());
+        iter
+    }
+
+    fn below_start(&self, key: &K) -> bool {
+        match self.range.start_bound() {
+            Bound::Included(start) => key.borrow() < start,
+            Bound::Excluded(start) => key.borrow() <= start,
+            Bound::Unbounded => false,
+        }
+    }
+
+    fn after_end(&self, key: &K) -> bool {
+        match self.range.end_bound() {
+            Bound::Included(end) => key.borrow() > end,
+            Bound::Excluded(end) => key.borrow() >= end,
+            Bound::Unbounded => false,
+        }
+    }
+
+    // a node below the start is skipped together with its left subtree
+    fn push_left(&mut self, mut node: Option<&'a TreeNode<K, V>>) {
+        while let Some(current) = node {
+            if self.below_start(&current.key) {
+                node = current.right.as_deref();
+            } else {
+                self.stack.push(current);
+                node = current.left.as_deref();
+            }
+        }
+    }
+}
+
+impl<'a, K, V, Q, R> Iterator for Range<'a, K, V, Q, R>
+where
+    K: Ord + Borrow<Q>,
+    Q: ?Sized + Ord,
+    R: RangeBounds<Q>,
+{
+    type Item = &'a TreeNode<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // everything that would follow is after the end too
+        if self.after_end(&node.key) {
+            self.stack.clear();
+            return None;
+        }
+        self.push_left(node.right.as_deref());
+
+        Some(node)
+    }
+}
+
+// node before its subtrees
+pub(crate) struct Preorder<'a, K: Ord, V> {
+    stack: Vec<&'a TreeNode<K, V>>,
+}
+
+impl<'a, K: Ord, V> Preorder<'a, K, V> {
+    pub(crate) fn new(link: &'a Link<K, V>) -> Self {
+        Preorder {
+            stack: link.as_deref().into_iter().collect(),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Preorder<'a, K, V> {
+    type Item = &'a TreeNode<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // the left subtree has to come out first
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+
+        Some(node)
+    }
+}
+
+// subtrees before their node
+pub(crate) struct Postorder<'a, K: Ord, V> {
+    // the flag tells whether the children were already pushed
+    stack: Vec<(&'a TreeNode<K, V>, bool)>,
+}
+
+impl<'a, K: Ord, V> Postorder<'a, K, V> {
+    pub(crate) fn new(link: &'a Link<K, V>) -> Self {
+        Postorder {
+            stack: link
+                .as_deref()
+                .map(|root| (root, false))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Postorder<'a, K, V> {
+    type Item = &'a TreeNode<K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(node);
+            }
+
+            self.stack.push((node, true));
+            self.stack
+                .extend(node.right.as_deref().map(|right| (right, false)));
+            self.stack
+                .extend(node.left.as_deref().map(|left| (left, false)));
+        }
+    }
+}
+
+// in-order with mutable values, keys stay borrowed immutably so the order holds
+pub(crate) struct InOrderMut<'a, K: Ord, V> {
+    // a node is kept split into its key, value and right subtree
+    stack: Vec<(&'a K, &'a mut V, &'a mut Link<K, V>)>,
+}
+
+impl<'a, K: Ord, V> InOrderMut<'a, K, V> {
+    pub(crate) fn new(link: &'a mut Link<K, V>) -> Self {
+        let mut iter = InOrderMut { stack: Vec::new() };
+        iter.push_left(link);
+        iter
+    }
+
+    fn push_left(&mut self, mut link: &'a mut Link<K, V>) {
+        while let Some(node) = link {
+            let TreeNode {
+                key,
+                value,
+                left,
+                right,
+                ..
+            } = &mut **node;
+            self.stack.push((key, value, right));
+            link = left;
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for InOrderMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value, right) = self.stack.pop()?;
+        self.push_left(right);
+
+        Some((key, value))
+    }
+}
+
+// in-order, takes the nodes out of the tree
+pub(crate) struct IntoIter<K: Ord, V> {
+    stack: Vec<Box<TreeNode<K, V>>>,
+}
+
+impl<K: Ord, V> IntoIter<K, V> {
+    pub(crate) fn new(link: Link<K, V>) -> Self {
+        let mut iter = IntoIter { stack: Vec::new() };
+        iter.push_left(link);
+        iter
+    }
+
+    fn push_left(&mut self, mut link: Link<K, V>) {
+        while let Some(mut node) = link {
+            link = node.left.take();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<K: Ord, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.stack.pop()?;
+        self.push_left(node.right.take());
+
+        Some((node.key, node.value))
+    }
+}
+
+impl<K: Ord, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        for node in self.stack.drain(..) {
+            drop_nodes(Some(node));
+        }
+    }
+}
@@ -0,0 +1,364 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::rc::Rc;
+
+// Tree that is never changed in place. insert and remove return a new tree
+// that copies the nodes on the path to the change and shares every other
+// subtree with the old one, so both stay usable and cloning is O(1).
+pub struct PersistentTree<T: Ord> {
+    root: Link<T>,
+    len: usize,
+}
+
+type Link<T> = Option<Rc<PersistentNode<T>>>;
+
+struct PersistentNode<T> {
+    value: T,
+    left: Link<T>,
+    right: Link<T>,
+}
+
+// a node on the way down and whether the way went to its left child
+type Step<'a, T> = (&'a PersistentNode<T>, bool);
+
+impl<T: Ord> PersistentTree<T> {
+    pub fn empty() -> PersistentTree<T> {
+        PersistentTree { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains<Q: ?Sized + Ord>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.get(value).is_some()
+    }
+
+    pub fn get<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        let mut node = &self.root;
+        while let Some(current) = node {
+            node = match value.cmp(current.value.borrow()) {
+                Ordering::Equal => return Some(&current.value),
+                Ordering::Less => &current.left,
+                Ordering::Greater => &current.right,
+            };
+        }
+
+        None
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+
+        Some(&node.value)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+
+        Some(&node.value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(&self.root);
+        iter
+    }
+
+    // nodes of this tree that are also part of the other one
+    pub fn shared_nodes(&self, other: &PersistentTree<T>) -> usize {
+        let theirs: HashSet<*const PersistentNode<T>> = other.nodes().collect();
+        self.nodes().filter(|node| theirs.contains(node)).count()
+    }
+
+    fn nodes(&self) -> impl Iterator<Item = *const PersistentNode<T>> + '_ {
+        let mut stack: Vec<&Rc<PersistentNode<T>>> = self.root.iter().collect();
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(node.left.iter().chain(node.right.iter()));
+            Some(Rc::as_ptr(node))
+        })
+    }
+}
+
+impl<T: Ord + Clone> PersistentTree<T> {
+    pub fn from(value: T) -> PersistentTree<T> {
+        PersistentTree::empty().insert(value)
+    }
+
+    // the tree itself is returned when the value is already in it
+    pub fn insert(&self, value: T) -> PersistentTree<T> {
+        let mut path = Vec::new();
+        let mut node = &self.root;
+        while let Some(current) = node {
+            let left = match value.cmp(&current.value) {
+                Ordering::Equal => return self.clone(),
+                Ordering::Less => true,
+                Ordering::Greater => false,
+            };
+            path.push((current.as_ref(), left));
+            node = if left { &current.left } else { &current.right };
+        }
+
+        let leaf = Rc::new(PersistentNode {
+            value,
+            left: None,
+            right: None,
+        });
+        PersistentTree {
+            root: rebuild(path, Some(leaf)),
+            len: self.len + 1,
+        }
+    }
+
+    // the tree itself is returned when the value is not in it
+    pub fn remove<Q: ?Sized + Ord>(&self, value: &Q) -> PersistentTree<T>
+    where
+        T: Borrow<Q>,
+    {
+        let mut path = Vec::new();
+        let mut node = &self.root;
+        let removed = loop {
+            let current = match node {
+                None => return self.clone(),
+                Some(current) => current,
+            };
+            let left = match value.cmp(current.value.borrow()) {
+                Ordering::Equal => break current,
+                Ordering::Less => true,
+                Ordering::Greater => false,
+            };
+            path.push((current.as_ref(), left));
+            node = if left { &current.left } else { &current.right };
+        };
+
+        // the smallest value of the right subtree takes the place of a node
+        // with both children
+        let replacement = match (&removed.left, &removed.right) {
+            (None, child) | (child, None) => child.clone(),
+            (Some(_), Some(right)) => {
+                let (successor, right) = without_min(right);
+                Some(Rc::new(PersistentNode {
+                    value: successor,
+                    left: removed.left.clone(),
+                    right,
+                }))
+            }
+        };
+
+        PersistentTree {
+            root: rebuild(path, replacement),
+            len: self.len - 1,
+        }
+    }
+
+    pub fn pop_min(&self) -> Option<(T, PersistentTree<T>)> {
+        let (min, root) = without_min(self.root.as_ref()?);
+        Some((
+            min,
+            PersistentTree {
+                root,
+                len: self.len - 1,
+            },
+        ))
+    }
+}
+
+// copies the nodes of the path from the bottom up, the last one gets the
+// given child in place of the one the path went to
+fn rebuild<T: Clone>(path: Vec<Step<T>>, mut child: Link<T>) -> Link<T> {
+    for (node, left) in path.into_iter().rev() {
+        let (left, right) = if left {
+            (child, node.right.clone())
+        } else {
+            (node.left.clone(), child)
+        };
+        child = Some(Rc::new(PersistentNode {
+            value: node.value.clone(),
+            left,
+            right,
+        }));
+    }
+
+    child
+}
+
+// the smallest value and a copy of the subtree without it
+fn without_min<T: Clone>(node: &Rc<PersistentNode<T>>) -> (T, Link<T>) {
+    let mut path = Vec::new();
+    let mut node = node.as_ref();
+    while let Some(left) = &node.left {
+        path.push((node, true));
+        node = left;
+    }
+
+    (node.value.clone(), rebuild(path, node.right.clone()))
+}
+
+impl<T: Ord> Clone for PersistentTree<T> {
+    fn clone(&self) -> Self {
+        PersistentTree {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Ord> Drop for PersistentTree<T> {
+    // nodes are freed one by one once nothing else shares them,
+    // the recursive drop of Rc would overflow the stack of a deep tree
+    fn drop(&mut self) {
+        let mut stack: Vec<Rc<PersistentNode<T>>> = self.root.take().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if let Ok(mut node) = Rc::try_unwrap(node) {
+                stack.extend(node.left.take());
+                stack.extend(node.right.take());
+            }
+        }
+    }
+}
+
+// in-order, smallest value first
+pub struct Iter<'a, T> {
+    stack: Vec<&'a PersistentNode<T>>,
+}
+
+impl<'a, T> Iter<'a, T> {
+    fn push_left(&mut self, mut node: &'a Link<T>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = &current.left;
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(&node.right);
+
+        Some(&node.value)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a PersistentTree<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord + Clone> FromIterator<T> for PersistentTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        iter.into_iter()
+            .fold(PersistentTree::empty(), |tree, value| tree.insert(value))
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for PersistentTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::thread;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    // snapshots after every step compared against full copies of a BTreeSet,
+    // after all the steps so that later ones can't have changed earlier ones
+    #[test]
+    fn snapshots_agree_with_btreeset_copies() {
+        let mut rng = thread_rng();
+        let mut snapshots = vec![PersistentTree::empty()];
+        let mut expected = vec![BTreeSet::new()];
+        for _ in 0..2_000 {
+            let tree = snapshots.last().unwrap();
+            let mut set = expected.last().unwrap().clone();
+            let value = rng.gen_range(0..500);
+            let next = if rng.gen_bool(0.6) {
+                set.insert(value);
+                tree.insert(value)
+            } else if rng.gen_bool(0.8) {
+                set.remove(&value);
+                tree.remove(&value)
+            } else {
+                match tree.pop_min() {
+                    Some((min, rest)) => {
+                        assert_eq!(Some(&min), set.pop_first().as_ref());
+                        rest
+                    }
+                    None => tree.clone(),
+                }
+            };
+
+            // only the path to the change is copied, and a random tree of at
+            // most 500 values is far less than 100 deep
+            assert!(next.len() - next.shared_nodes(tree) < 100);
+            snapshots.push(next);
+            expected.push(set);
+        }
+
+        for (tree, set) in snapshots.iter().zip(&expected) {
+            assert_eq!(tree.len(), set.len());
+            assert!(tree.iter().eq(set.iter()));
+            assert_eq!(tree.first(), set.first());
+            assert_eq!(tree.last(), set.last());
+        }
+
+        let last = snapshots.last().unwrap();
+        let clone = last.clone();
+        assert_eq!(clone.shared_nodes(last), last.len());
+    }
+
+    // sorted input makes the tree a list, the stack is small so that any
+    // recursion over the whole depth, like the default drop of Rc, overflows it
+    #[test]
+    fn deep_tree_without_recursion() {
+        thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(deep_tree)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    fn deep_tree() {
+        // every insertion copies the whole list, the run is quadratic
+        const LENGTH: u32 = 5_000;
+
+        let tree: PersistentTree<_> = (0..LENGTH).collect();
+        let smaller = tree.remove(&0);
+        drop(tree);
+        assert_eq!(smaller.first(), Some(&1));
+        assert_eq!(smaller.last(), Some(&(LENGTH - 1)));
+        drop(smaller);
+    }
+}
@@ -0,0 +1,241 @@
+use std::cmp::Ordering;
+use std::iter::Peekable;
+
+use crate::iter::Iter;
+use crate::node;
+use crate::tree::Tree;
+
+// Set operations between two trees. They merge the two sorted sequences in one
+// pass, either lazily over references or consuming both trees into a new,
+// balanced one.
+
+// the value taken from the first and the second sequence
+type Step<T> = (Option<T>, Option<T>);
+
+// walks two sorted sequences side by side
+struct Merge<I: Iterator> {
+    a: Peekable<I>,
+    b: Peekable<I>,
+}
+
+impl<I: Iterator> Merge<I>
+where
+    I::Item: Ord,
+{
+    fn new(a: I, b: I) -> Self {
+        Merge {
+            a: a.peekable(),
+            b: b.peekable(),
+        }
+    }
+
+    // the smallest value, taken from the sequences that have it
+    fn next(&mut self) -> Option<Step<I::Item>> {
+        let ordering = match (self.a.peek(), self.b.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => a.cmp(b),
+        };
+
+        Some(match ordering {
+            Ordering::Less => (self.a.next(), None),
+            Ordering::Greater => (None, self.b.next()),
+            Ordering::Equal => (self.a.next(), self.b.next()),
+        })
+    }
+}
+
+// values in either tree
+pub struct Union<I: Iterator> {
+    merge: Merge<I>,
+}
+
+impl<I: Iterator> Iterator for Union<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (a, b) = self.merge.next()?;
+        a.or(b)
+    }
+}
+
+// values in both trees
+pub struct Intersection<I: Iterator> {
+    merge: Merge<I>,
+}
+
+impl<I: Iterator> Iterator for Intersection<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(a), Some(_)) = self.merge.next()? {
+                return Some(a);
+            }
+        }
+    }
+}
+
+// values in the first tree only
+pub struct Difference<I: Iterator> {
+    merge: Merge<I>,
+}
+
+impl<I: Iterator> Iterator for Difference<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let (Some(a), None) = self.merge.next()? {
+                return Some(a);
+            }
+        }
+    }
+}
+
+// values in exactly one of the trees
+pub struct SymmetricDifference<I: Iterator> {
+    merge: Merge<I>,
+}
+
+impl<I: Iterator> Iterator for SymmetricDifference<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.merge.next()? {
+                (Some(value), None) | (None, Some(value)) => return Some(value),
+                _ => (),
+            }
+        }
+    }
+}
+
+impl<T: Ord> Tree<T> {
+    pub fn union<'a>(&'a self, other: &'a Tree<T>) -> Union<Iter<'a, T>> {
+        Union {
+            merge: Merge::new(self.iter(), other.iter()),
+        }
+    }
+
+    pub fn intersection<'a>(&'a self, other: &'a Tree<T>) -> Intersection<Iter<'a, T>> {
+        Intersection {
+            merge: Merge::new(self.iter(), other.iter()),
+        }
+    }
+
+    pub fn difference<'a>(&'a self, other: &'a Tree<T>) -> Difference<Iter<'a, T>> {
+        Difference {
+            merge: Merge::new(self.iter(), other.iter()),
+        }
+    }
+
+    pub fn symmetric_difference<'a>(
+        &'a self,
+        other: &'a Tree<T>,
+    ) -> SymmetricDifference<Iter<'a, T>> {
+        SymmetricDifference {
+            merge: Merge::new(self.iter(), other.iter()),
+        }
+    }
+
+    pub fn is_subset(&self, other: &Tree<T>) -> bool {
+        self.len() <= other.len() && self.difference(other).next().is_none()
+    }
+
+    pub fn is_superset(&self, other: &Tree<T>) -> bool {
+        other.is_subset(self)
+    }
+
+    pub fn is_disjoint(&self, other: &Tree<T>) -> bool {
+        self.intersection(other).next().is_none()
+    }
+
+    // the consuming versions build a balanced tree in linear time
+    pub fn into_union(self, other: Tree<T>) -> Tree<T> {
+        Tree::from_sorted(Union {
+            merge: Merge::new(self.into_iter(), other.into_iter()),
+        })
+    }
+
+    pub fn into_intersection(self, other: Tree<T>) -> Tree<T> {
+        Tree::from_sorted(Intersection {
+            merge: Merge::new(self.into_iter(), other.into_iter()),
+        })
+    }
+
+    pub fn into_difference(self, other: Tree<T>) -> Tree<T> {
+        Tree::from_sorted(Difference {
+            merge: Merge::new(self.into_iter(), other.into_iter()),
+        })
+    }
+
+    pub fn into_symmetric_difference(self, other: Tree<T>) -> Tree<T> {
+        Tree::from_sorted(SymmetricDifference {
+            merge: Merge::new(self.into_iter(), other.into_iter()),
+        })
+    }
+
+    // the values have to be strictly increasing
+    fn from_sorted<I: Iterator<Item = T>>(values: I) -> Tree<T> {
+        let values: Vec<T> = values.collect();
+        let len = values.len();
+        Tree {
+            root: node::build_balanced(&mut values.into_iter().map(|value| (value, ())), len),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    // lazy and consuming set operations compared against BTreeSet
+    #[test]
+    fn set_operations_agree_with_btreeset() {
+        let mut rng = thread_rng();
+        for _ in 0..20 {
+            let a: BTreeSet<u8> = (0..rng.gen_range(0..200)).map(|_| rng.gen()).collect();
+            let b: BTreeSet<u8> = (0..rng.gen_range(0..200)).map(|_| rng.gen()).collect();
+            let tree = |set: &BTreeSet<u8>| set.iter().copied().collect::<Tree<_>>();
+            let (x, y) = (tree(&a), tree(&b));
+
+            assert!(x.union(&y).eq(a.union(&b)));
+            assert!(x.intersection(&y).eq(a.intersection(&b)));
+            assert!(x.difference(&y).eq(a.difference(&b)));
+            assert!(x.symmetric_difference(&y).eq(a.symmetric_difference(&b)));
+            assert_eq!(x.is_subset(&y), a.is_subset(&b));
+            assert_eq!(x.is_disjoint(&y), a.is_disjoint(&b));
+            assert!(x.is_subset(&x.union(&y).copied().collect()));
+
+            let results = [
+                (tree(&a).into_union(tree(&b)), &a | &b),
+                (tree(&a).into_intersection(tree(&b)), &a & &b),
+                (tree(&a).into_difference(tree(&b)), &a - &b),
+                (tree(&a).into_symmetric_difference(tree(&b)), &a ^ &b),
+            ];
+            for (result, expected) in results {
+                assert!(result.is_valid());
+                assert!(result.iter().eq(expected.iter()));
+                assert_eq!(result.balance_report().unbalanced(), 0);
+            }
+        }
+    }
+}
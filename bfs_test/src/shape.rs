@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::node::{self, TreeNode};
+use crate::tree::Tree;
+
+// Diagnostics of the shape of a Tree and drawing it as text.
+
+// levels drawn by Display, deeper subtrees are only counted
+pub const DISPLAY_DEPTH: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceReport {
+    pub len: usize,
+    pub height: usize,
+    // height of a perfectly balanced tree with the same values
+    pub optimal_height: usize,
+    // balance factor (left height - right height) -> number of nodes with it
+    pub factors: BTreeMap<isize, usize>,
+}
+
+impl BalanceReport {
+    // nodes whose subtrees differ in height by more than one
+    pub fn unbalanced(&self) -> usize {
+        self.factors
+            .iter()
+            .filter(|(factor, _)| factor.abs() > 1)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    pub fn max_imbalance(&self) -> usize {
+        self.factors
+            .keys()
+            .map(|factor| factor.unsigned_abs())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+impl fmt::Display for BalanceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} values, height {} (optimal {})",
+            self.len, self.height, self.optimal_height
+        )?;
+        writeln!(
+            f,
+            "{} unbalanced nodes, largest difference {}",
+            self.unbalanced(),
+            self.max_imbalance()
+        )?;
+        for (factor, count) in &self.factors {
+            writeln!(f, "{:>+5}: {}", factor, count)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Ord> Tree<T> {
+    pub fn leaf_count(&self) -> usize {
+        node::InOrder::new(&self.root)
+            .filter(|node| node.left.is_none() && node.right.is_none())
+            .count()
+    }
+
+    // number of values on every level, from the root
+    pub fn level_widths(&self) -> Vec<usize> {
+        self.levels().map(|level| level.len()).collect()
+    }
+
+    pub fn balance_report(&self) -> BalanceReport {
+        let mut factors = BTreeMap::new();
+        // post-order visits both subtrees of a node right before it,
+        // so their heights are on the top of the stack
+        let mut heights: Vec<usize> = Vec::new();
+        for node in node::Postorder::new(&self.root) {
+            let right = if node.right.is_some() {
+                heights.pop().unwrap()
+            } else {
+                0
+            };
+            let left = if node.left.is_some() {
+                heights.pop().unwrap()
+            } else {
+                0
+            };
+            *factors.entry(left as isize - right as isize).or_insert(0) += 1;
+            heights.push(1 + left.max(right));
+        }
+
+        let len = self.len();
+        BalanceReport {
+            len,
+            height: heights.pop().unwrap_or(0),
+            optimal_height: (usize::BITS - len.leading_zeros()) as usize,
+            factors,
+        }
+    }
+
+    // draws the tree top-down, left child first, at most max_depth levels
+    pub fn pretty(&self, max_depth: usize) -> Pretty<'_, T> {
+        Pretty {
+            tree: self,
+            max_depth,
+        }
+    }
+}
+
+// node, what is drawn before it, depth
+type Pending<'a, T> = (Option<&'a TreeNode<T, ()>>, String, usize);
+
+pub struct Pretty<'a, T: Ord> {
+    tree: &'a Tree<T>,
+    max_depth: usize,
+}
+
+impl<'a, T: Ord + fmt::Display> fmt::Display for Pretty<'a, T> {
+    // 4
+    // ├── 2
+    // │   ├── 1
+    // │   └── 3
+    // └── 6
+    //     ├── ·
+    //     └── 7 … 2 more
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let root = match &self.tree.root {
+            None => return writeln!(f, "(empty)"),
+            Some(root) => root.as_ref(),
+        };
+
+        let mut stack: Vec<Pending<T>> = vec![(Some(root), String::new(), 0)];
+        while let Some((node, prefix, depth)) = stack.pop() {
+            let node = match node {
+                None => {
+                    writeln!(f, "{}·", prefix)?;
+                    continue;
+                }
+                Some(node) => node,
+            };
+
+            let has_children = node.left.is_some() || node.right.is_some();
+            if depth + 1 >= self.max_depth && has_children {
+                writeln!(f, "{}{} … {} more", prefix, node.key, node.size - 1)?;
+                continue;
+            }
+            writeln!(f, "{}{}", prefix, node.key)?;
+            if !has_children {
+                continue;
+            }
+
+            // the children continue the lines of the ancestors
+            let indent = prefix.replace("├── ", "│   ").replace("└── ", "    ");
+            stack.push((node.right.as_deref(), format!("{}└── ", indent), depth + 1));
+            stack.push((node.left.as_deref(), format!("{}├── ", indent), depth + 1));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Ord + fmt::Display> fmt::Display for Tree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pretty(DISPLAY_DEPTH))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //         50
+    //     30      70
+    //   20  40  60  80
+    //      35
+    fn sample() -> Tree<i32> {
+        [50, 30, 70, 20, 40, 60, 80, 35].into_iter().collect()
+    }
+
+    #[test]
+    fn leaves_and_level_widths() {
+        let tree = sample();
+        assert_eq!(tree.leaf_count(), 4);
+        assert_eq!(tree.level_widths(), [1, 2, 4, 1]);
+
+        let empty: Tree<i32> = Tree::empty();
+        assert_eq!(empty.leaf_count(), 0);
+        assert!(empty.level_widths().is_empty());
+    }
+
+    #[test]
+    fn balance_report_counts_the_factors() {
+        let report = sample().balance_report();
+        assert_eq!(
+            (report.len, report.height, report.optimal_height),
+            (8, 4, 4)
+        );
+        assert_eq!(report.factors, BTreeMap::from([(-1, 1), (0, 5), (1, 2)]));
+        assert_eq!(report.unbalanced(), 0);
+        assert_eq!(report.max_imbalance(), 1);
+
+        let list: Tree<i32> = (1..=3).collect();
+        assert_eq!(
+            list.balance_report().to_string(),
+            concat!(
+                "3 values, height 3 (optimal 2)\n",
+                "1 unbalanced nodes, largest difference 2\n",
+                "   -2: 1\n",
+                "   -1: 1\n",
+                "   +0: 1\n",
+            )
+        );
+    }
+
+    #[test]
+    fn display_draws_the_whole_small_tree() {
+        assert_eq!(
+            sample().to_string(),
+            concat!(
+                "50\n",
+                "├── 30\n",
+                "│   ├── 20\n",
+                "│   └── 40\n",
+                "│       ├── 35\n",
+                "│       └── ·\n",
+                "└── 70\n",
+                "    ├── 60\n",
+                "    └── 80\n",
+            )
+        );
+        assert_eq!(Tree::<i32>::empty().to_string(), "(empty)\n");
+    }
+
+    #[test]
+    fn pretty_counts_the_levels_below_the_limit() {
+        let tree = sample();
+        assert_eq!(
+            tree.pretty(2).to_string(),
+            "50\n├── 30 … 3 more\n└── 70 … 2 more\n"
+        );
+        assert_eq!(tree.pretty(1).to_string(), "50 … 7 more\n");
+    }
+}
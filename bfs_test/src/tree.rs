@@ -0,0 +1,299 @@
+use std::borrow::Borrow;
+use std::fmt;
+
+use crate::node::{self, Link};
+
+pub struct Tree<T: Ord> {
+    pub(crate) root: Link<T, ()>,
+}
+
+impl<T: Ord> Tree<T> {
+    pub fn from(value: T) -> Tree<T> {
+        let mut tree = Tree::empty();
+        tree.add(value);
+        tree
+    }
+
+    pub fn empty() -> Tree<T> {
+        Tree { root: None }
+    }
+
+    pub fn add(&mut self, value: T) {
+        if !self.contains(&value) {
+            node::insert(&mut self.root, value, ());
+        }
+    }
+
+    // every node knows the size of its subtree
+    pub fn len(&self) -> usize {
+        node::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // lookups take anything the values can be borrowed as, like in BTreeSet,
+    // e.g. a &str for a Tree<String>
+    pub fn contains<Q: ?Sized + Ord>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.get(value).is_some()
+    }
+
+    // the stored value equal to the given one
+    pub fn get<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        node::find(&self.root, value).map(|node| &node.key)
+    }
+
+    // smallest value
+    pub fn first(&self) -> Option<&T> {
+        node::first(&self.root).map(|node| &node.key)
+    }
+
+    // largest value
+    pub fn last(&self) -> Option<&T> {
+        node::last(&self.root).map(|node| &node.key)
+    }
+
+    // largest value less than or equal to the given one
+    pub fn floor<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        node::floor(&self.root, value).map(|node| &node.key)
+    }
+
+    // smallest value greater than or equal to the given one
+    pub fn ceiling<Q: ?Sized + Ord>(&self, value: &Q) -> Option<&T>
+    where
+        T: Borrow<Q>,
+    {
+        node::ceiling(&self.root, value).map(|node| &node.key)
+    }
+
+    pub fn remove<Q: ?Sized + Ord>(&mut self, value: &Q) -> Option<T>
+    where
+        T: Borrow<Q>,
+    {
+        node::remove(&mut self.root, value).map(|(value, _)| value)
+    }
+
+    // k-th smallest value, counted from 0
+    pub fn select(&self, k: usize) -> Option<&T> {
+        node::select(&self.root, k).map(|node| &node.key)
+    }
+
+    // number of values less than the given one, the index it has or would have
+    pub fn rank<Q: ?Sized + Ord>(&self, value: &Q) -> usize
+    where
+        T: Borrow<Q>,
+    {
+        node::rank(&self.root, value)
+    }
+
+    // moves the values greater than or equal to the given one into a new tree
+    pub fn split_off<Q: ?Sized + Ord>(&mut self, value: &Q) -> Tree<T>
+    where
+        T: Borrow<Q>,
+    {
+        Tree {
+            root: node::split_off(&mut self.root, value),
+        }
+    }
+
+    pub fn pop_min(&mut self) -> Option<T> {
+        node::pop_first(&mut self.root).map(|(value, _)| value)
+    }
+
+    pub fn pop_max(&mut self) -> Option<T> {
+        node::pop_last(&mut self.root).map(|(value, _)| value)
+    }
+
+    // number of levels, 0 for an empty tree
+    pub fn height(&self) -> usize {
+        self.levels().count()
+    }
+
+    pub fn clear(&mut self) {
+        node::drop_nodes(self.root.take());
+    }
+
+    // values are in order and without duplicates, subtree sizes are right
+    pub fn is_valid(&self) -> bool {
+        self.iter().zip(self.iter().skip(1)).all(|(a, b)| a < b)
+            && node::InOrder::new(&self.root)
+                .all(|node| node.size == 1 + node::size(&node.left) + node::size(&node.right))
+    }
+}
+
+impl<T: Ord> Drop for Tree<T> {
+    fn drop(&mut self) {
+        node::drop_nodes(self.root.take());
+    }
+}
+
+impl<T: Ord + fmt::Debug> fmt::Debug for Tree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::ops::Bound;
+    use std::thread;
+
+    use rand::prelude::*;
+
+    use super::*;
+
+    // random insertions and removals compared against BTreeSet
+    #[test]
+    fn random_removals_agree_with_btreeset() {
+        let mut rng = thread_rng();
+        let mut tree = Tree::empty();
+        let mut expected = BTreeSet::new();
+        for _ in 0..10_000 {
+            let value = rng.gen::<i8>();
+            match rng.gen_range(0..8) {
+                0..=3 => {
+                    tree.add(value);
+                    expected.insert(value);
+                }
+                4..=5 => assert_eq!(tree.remove(&value), expected.take(&value)),
+                6 => assert_eq!(tree.pop_min(), expected.pop_first()),
+                _ => assert_eq!(tree.pop_max(), expected.pop_last()),
+            }
+
+            assert!(tree.is_valid());
+            assert_eq!(tree.len(), expected.len());
+        }
+
+        assert!(tree.iter().eq(expected.iter()));
+        tree.clear();
+        assert_eq!(tree.pop_min(), None);
+    }
+
+    // sorted input makes the tree a list, every operation has to walk it in a
+    // loop, the stack is small so that any recursion over the whole depth
+    // overflows it
+    #[test]
+    fn deep_tree_without_recursion() {
+        thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(deep_tree)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    fn deep_tree() {
+        // insertion into a list is linear, the whole run is quadratic
+        const LENGTH: u32 = 15_000;
+
+        let mut tree: Tree<_> = (0..LENGTH).collect();
+        assert!(tree.contains(&(LENGTH - 1)));
+        assert!(!tree.contains(&LENGTH));
+        assert_eq!(
+            format!("{:?}", tree),
+            format!("{:?}", (0..LENGTH).collect::<BTreeSet<_>>())
+        );
+        assert_eq!(tree.pop_max(), Some(LENGTH - 1));
+        drop(tree);
+
+        let tree: Tree<_> = (0..LENGTH).collect();
+        let mut iter = tree.into_iter();
+        assert_eq!(iter.next(), Some(0));
+        drop(iter);
+    }
+
+    // floor, ceiling, first and last compared against BTreeSet
+    #[test]
+    fn lookups_agree_with_btreeset() {
+        let mut rng = thread_rng();
+        let values: BTreeSet<i16> = (0..1_000).map(|_| rng.gen_range(-2_000..2_000)).collect();
+        let tree: Tree<_> = values.iter().copied().collect();
+        assert_eq!(tree.first(), values.first());
+        assert_eq!(tree.last(), values.last());
+        for value in -2_100..2_100 {
+            assert_eq!(tree.get(&value), values.get(&value));
+            assert_eq!(tree.floor(&value), values.range(..=value).next_back());
+            assert_eq!(tree.ceiling(&value), values.range(value..).next());
+        }
+
+        // no String has to be allocated for a lookup
+        let words: Tree<String> = ["bfs", "tree", "node"]
+            .map(String::from)
+            .into_iter()
+            .collect();
+        assert!(words.contains("tree"));
+        assert!(!words.contains("leaf"));
+        assert_eq!(words.ceiling("c").map(String::as_str), Some("node"));
+        assert!(words
+            .range::<str, _>((Bound::Included("c"), Bound::Excluded("t")))
+            .eq(["node"].iter()));
+    }
+
+    // ranges and splits compared against BTreeSet
+    #[test]
+    fn ranges_and_splits_agree_with_btreeset() {
+        let mut rng = thread_rng();
+        let values: BTreeSet<i16> = (0..1_000).map(|_| rng.gen_range(-2_000..2_000)).collect();
+        let tree: Tree<_> = values.iter().copied().collect();
+        for _ in 0..100 {
+            let (a, b) = (rng.gen_range(-2_100..2_100), rng.gen_range(-2_100..2_100));
+            let (start, end) = (a.min(b), a.max(b));
+            assert!(tree.range(start..end).eq(values.range(start..end)));
+            assert!(tree.range(start..=end).eq(values.range(start..=end)));
+            assert!(tree.range(..end).eq(values.range(..end)));
+            assert!(tree.range(start..).eq(values.range(start..)));
+        }
+        assert!(tree.range(..).eq(values.iter()));
+
+        let mut less = tree;
+        let mut expected_less = values;
+        for _ in 0..10 {
+            let key = rng.gen_range(-2_100..2_100);
+            let greater = less.split_off(&key);
+            let expected_greater = expected_less.split_off(&key);
+            assert!(less.is_valid() && greater.is_valid());
+            assert!(less.iter().eq(expected_less.iter()));
+            assert!(greater.iter().eq(expected_greater.iter()));
+        }
+    }
+
+    // select and rank compared against the sorted values, also after splits
+    #[test]
+    fn select_and_rank_agree_with_a_sorted_vec() {
+        let mut rng = thread_rng();
+        let mut tree = Tree::empty();
+        for _ in 0..2_000 {
+            tree.add(rng.gen_range(0..5_000));
+            if rng.gen_bool(0.3) {
+                tree.remove(&rng.gen_range(0..5_000));
+            }
+        }
+        let mut greater = tree.split_off(&2_500);
+        greater.add(2_500);
+        assert!(tree.is_valid() && greater.is_valid());
+
+        for tree in [tree, greater] {
+            let sorted: Vec<i32> = tree.iter().copied().collect();
+            assert_eq!(tree.len(), sorted.len());
+            for (index, value) in sorted.iter().enumerate() {
+                assert_eq!(tree.select(index), Some(value));
+                assert_eq!(tree.rank(value), index);
+            }
+            assert_eq!(tree.select(sorted.len()), None);
+            for value in -1..5_001 {
+                assert_eq!(tree.rank(&value), sorted.partition_point(|x| *x < value));
+            }
+        }
+    }
+}
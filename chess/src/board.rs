@@ -0,0 +1,171 @@
+use crate::solution::{Error, Piece, PieceType, Tiles, Turn, MAX_TILES_SIZE};
+
+// Board dimensions, starting position and pieces a pawn can promote to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardSetup {
+    width: usize,
+    height: usize,
+    // ranks[0] is white's first rank, black's ranks are mirrored
+    ranks: Vec<Vec<Option<PieceType>>>,
+    promotions: Vec<PieceType>,
+}
+
+const STANDARD_PROMOTIONS: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+impl BoardSetup {
+    pub fn new(
+        width: usize,
+        height: usize,
+        ranks: Vec<Vec<Option<PieceType>>>,
+        promotions: Vec<PieceType>,
+    ) -> Result<Self, Error> {
+        if width == 0 || height == 0 || width > MAX_TILES_SIZE || height > MAX_TILES_SIZE {
+            return Err(Error::PositionOutOfBounds);
+        }
+        // the armies must not overlap
+        if ranks.len() * 2 > height || ranks.iter().any(|rank| rank.len() != width) {
+            return Err(Error::InvalidSetup);
+        }
+
+        Ok(BoardSetup {
+            width,
+            height,
+            ranks,
+            promotions,
+        })
+    }
+
+    // board without a starting position, standard promotions
+    pub fn empty(width: usize, height: usize) -> Result<Self, Error> {
+        BoardSetup::new(width, height, Vec::new(), STANDARD_PROMOTIONS.to_vec())
+    }
+
+    pub fn standard() -> Self {
+        use PieceType::*;
+        BoardSetup::new(
+            8,
+            8,
+            vec![
+                vec![
+                    Some(Rook),
+                    Some(Knight),
+                    Some(Bishop),
+                    Some(Queen),
+                    Some(King),
+                    Some(Bishop),
+                    Some(Knight),
+                    Some(Rook),
+                ],
+                vec![Some(Pawn); 8],
+            ],
+            STANDARD_PROMOTIONS.to_vec(),
+        )
+        .unwrap()
+    }
+
+    // 10x8
+    pub fn capablanca() -> Self {
+        use PieceType::*;
+        BoardSetup::new(
+            10,
+            8,
+            vec![
+                vec![
+                    Some(Rook),
+                    Some(Knight),
+                    Some(Archbishop),
+                    Some(Bishop),
+                    Some(Queen),
+                    Some(King),
+                    Some(Bishop),
+                    Some(Chancellor),
+                    Some(Knight),
+                    Some(Rook),
+                ],
+                vec![Some(Pawn); 10],
+            ],
+            vec![Queen, Chancellor, Archbishop, Rook, Bishop, Knight],
+        )
+        .unwrap()
+    }
+
+    // 10x10, pawns start on the third rank
+    pub fn grand() -> Self {
+        use PieceType::*;
+        let mut first_rank = vec![None; 10];
+        first_rank[0] = Some(Rook);
+        first_rank[9] = Some(Rook);
+
+        BoardSetup::new(
+            10,
+            10,
+            vec![
+                first_rank,
+                vec![
+                    None,
+                    Some(Knight),
+                    Some(Bishop),
+                    Some(Queen),
+                    Some(King),
+                    Some(Chancellor),
+                    Some(Archbishop),
+                    Some(Bishop),
+                    Some(Knight),
+                    None,
+                ],
+                vec![Some(Pawn); 10],
+            ],
+            vec![Queen, Chancellor, Archbishop, Rook, Bishop, Knight],
+        )
+        .unwrap()
+    }
+
+    // the setup of a known board size: standard, Capablanca or Grand Chess
+    pub fn for_size(width: usize, height: usize) -> Option<Self> {
+        [
+            BoardSetup::standard(),
+            BoardSetup::capablanca(),
+            BoardSetup::grand(),
+        ]
+        .into_iter()
+        .find(|setup| setup.width == width && setup.height == height)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn height(&self) -> usize {
+        self.height
+    }
+    pub fn promotions(&self) -> &[PieceType] {
+        &self.promotions
+    }
+
+    // row of white's pawns at the start, they can move by two squares from there
+    pub fn pawn_row(&self) -> usize {
+        self.ranks
+            .iter()
+            .position(|rank| rank.contains(&Some(PieceType::Pawn)))
+            .unwrap_or(1)
+    }
+
+    pub fn starting_tiles(&self) -> Tiles {
+        let mut tiles = vec![vec![None; self.width]; self.height];
+        for (row, rank) in self.ranks.iter().enumerate() {
+            for (column, piece_type) in rank.iter().enumerate() {
+                if let Some(piece_type) = piece_type {
+                    tiles[row][column] = Some(Piece::new(Turn::WhitePlays, *piece_type));
+                    tiles[self.height - 1 - row][column] =
+                        Some(Piece::new(Turn::BlackPlays, *piece_type));
+                }
+            }
+        }
+
+        tiles
+    }
+}
@@ -0,0 +1,369 @@
+use crate::movegen::Move;
+use crate::solution::{ChessGame, Piece, PieceType, Position, Tiles, Turn};
+use crate::variants::Rules;
+
+// Castling on boards of any width. The king moves to the second file from the
+// corner (g1 or c1, i1 or c1 on the 10x8 board) and the rook of that corner
+// jumps to the square next to it on the inner side.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CastlingSide {
+    // towards the last file
+    Kingside,
+    // towards the a-file
+    Queenside,
+}
+
+impl CastlingSide {
+    pub const BOTH: [CastlingSide; 2] = [CastlingSide::Kingside, CastlingSide::Queenside];
+
+    fn index(&self) -> usize {
+        match self {
+            CastlingSide::Kingside => 0,
+            CastlingSide::Queenside => 1,
+        }
+    }
+    fn rook_file(&self, width: usize) -> usize {
+        match self {
+            CastlingSide::Kingside => width - 1,
+            CastlingSide::Queenside => 0,
+        }
+    }
+    // None when the board is too narrow to castle on this side
+    fn king_destination_file(&self, width: usize) -> Option<usize> {
+        match self {
+            CastlingSide::Kingside => width.checked_sub(2),
+            CastlingSide::Queenside => Some(2),
+        }
+        .filter(|file| *file < width)
+    }
+    fn rook_destination_file(&self, width: usize) -> Option<usize> {
+        match self {
+            CastlingSide::Kingside => width.checked_sub(3),
+            CastlingSide::Queenside => Some(3),
+        }
+        .filter(|file| *file < width)
+    }
+}
+
+// rows the kings and rooks castle on
+pub(crate) fn home_row(height: usize, color: Turn) -> usize {
+    match color {
+        Turn::WhitePlays => 0,
+        Turn::BlackPlays => height - 1,
+    }
+}
+
+// whether the king and the rook of a side have not moved yet
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct CastlingRights {
+    white: [bool; 2],
+    black: [bool; 2],
+}
+
+impl CastlingRights {
+    pub fn none() -> Self {
+        Self::default()
+    }
+    pub fn all() -> Self {
+        CastlingRights {
+            white: [true; 2],
+            black: [true; 2],
+        }
+    }
+    // a side may castle when its king starts on the first rank and a rook
+    // starts in the corner
+    pub(crate) fn from_tiles(tiles: &Tiles) -> Self {
+        let mut rights = CastlingRights::none();
+        let (width, height) = (tiles[0].len(), tiles.len());
+        for color in [Turn::WhitePlays, Turn::BlackPlays] {
+            let row = &tiles[home_row(height, color)];
+            if !row.contains(&Some(Piece::new(color, PieceType::King))) {
+                continue;
+            }
+            for side in CastlingSide::BOTH {
+                let rook = row[side.rook_file(width)];
+                rights.set(
+                    color,
+                    side,
+                    rook == Some(Piece::new(color, PieceType::Rook)),
+                );
+            }
+        }
+
+        rights
+    }
+
+    pub fn has(&self, color: Turn, side: CastlingSide) -> bool {
+        self.of(color)[side.index()]
+    }
+    pub fn set(&mut self, color: Turn, side: CastlingSide, allowed: bool) {
+        let rights = match color {
+            Turn::WhitePlays => &mut self.white,
+            Turn::BlackPlays => &mut self.black,
+        };
+        rights[side.index()] = allowed;
+    }
+
+    fn of(&self, color: Turn) -> &[bool; 2] {
+        match color {
+            Turn::WhitePlays => &self.white,
+            Turn::BlackPlays => &self.black,
+        }
+    }
+
+    // rights left after the piece made the move, tiles are the position after it
+    pub(crate) fn update(&mut self, tiles: &Tiles, mv: &Move, moved: Piece) {
+        let (width, height) = (tiles[0].len(), tiles.len());
+        for color in [Turn::WhitePlays, Turn::BlackPlays] {
+            let row = home_row(height, color);
+            let king = Piece::new(color, PieceType::King);
+            let king_moved = moved == king || !tiles[row].contains(&Some(king));
+            for side in CastlingSide::BOTH {
+                let corner = Position {
+                    x: side.rook_file(width),
+                    y: row,
+                };
+                // the rook may also have been captured or blown up
+                let rook_gone = mv.src == corner
+                    || tiles[row][corner.x] != Some(Piece::new(color, PieceType::Rook));
+                if king_moved || rook_gone {
+                    self.set(color, side, false);
+                }
+            }
+        }
+    }
+}
+
+// a king moving along its rank by more than one square castles
+pub(crate) fn castling_side(tiles: &Tiles, mv: &Move) -> Option<CastlingSide> {
+    let piece = tiles[mv.src.get_y()][mv.src.get_x()]?;
+    if piece.piece_type() != PieceType::King
+        || mv.src.get_y() != mv.dst.get_y()
+        || mv.src.get_x().abs_diff(mv.dst.get_x()) < 2
+    {
+        return None;
+    }
+
+    Some(if mv.dst.get_x() > mv.src.get_x() {
+        CastlingSide::Kingside
+    } else {
+        CastlingSide::Queenside
+    })
+}
+
+// where the rook goes from and to when the king castles with the move
+pub(crate) fn rook_move(tiles: &Tiles, mv: &Move) -> Option<(Position, Position)> {
+    let side = castling_side(tiles, mv)?;
+    let width = tiles[0].len();
+    let y = mv.src.get_y();
+    let king = tiles[y][mv.src.get_x()]?;
+    if tiles[y][side.rook_file(width)] != Some(Piece::new(king.color(), PieceType::Rook)) {
+        return None;
+    }
+
+    Some((
+        Position {
+            x: side.rook_file(width),
+            y,
+        },
+        Position {
+            x: side.rook_destination_file(width)?,
+            y,
+        },
+    ))
+}
+
+// the squares between the king and the rook and their destinations are
+// empty, and the king is not in check on its square, on the way and at the end
+pub(crate) fn castling_moves<R: Rules + ?Sized>(rules: &R, game: &ChessGame) -> Vec<Move> {
+    let color = game.current_player();
+    let tiles = &game.tiles;
+    let (width, row) = (game.width(), home_row(game.height(), color));
+    let king_piece = Piece::new(color, PieceType::King);
+    let king = match tiles[row]
+        .iter()
+        .position(|field| *field == Some(king_piece))
+    {
+        Some(x) => x,
+        None => return Vec::new(),
+    };
+    if rules.in_check(tiles, color) {
+        return Vec::new();
+    }
+
+    let mut moves = Vec::new();
+    for side in CastlingSide::BOTH {
+        if !game.castling_rights().has(color, side) {
+            continue;
+        }
+        let rook = side.rook_file(width);
+        let (king_destination, rook_destination) = match (
+            side.king_destination_file(width),
+            side.rook_destination_file(width),
+        ) {
+            (Some(king_destination), Some(rook_destination)) => {
+                (king_destination, rook_destination)
+            }
+            _ => continue,
+        };
+        if tiles[row][rook] != Some(Piece::new(color, PieceType::Rook))
+            || king.abs_diff(king_destination) < 2
+        {
+            continue;
+        }
+
+        let files = [king, rook, king_destination, rook_destination];
+        let (low, high) = (*files.iter().min().unwrap(), *files.iter().max().unwrap());
+        if (low..=high).any(|x| x != king && x != rook && tiles[row][x].is_some()) {
+            continue;
+        }
+
+        let (from, to) = (king.min(king_destination), king.max(king_destination));
+        let attacked = (from..=to).filter(|x| *x != king).any(|x| {
+            let mut after = tiles.clone();
+            after[row][king] = None;
+            after[row][x] = Some(king_piece);
+            rules.in_check(&after, color)
+        });
+        if !attacked {
+            moves.push(Move::new(
+                Position { x: king, y: row },
+                Position {
+                    x: king_destination,
+                    y: row,
+                },
+            ));
+        }
+    }
+
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::events::GameEvent;
+
+    fn pos(square: &str) -> Position {
+        Position::try_from(square).unwrap()
+    }
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for mv in moves {
+            let (src, dst) = mv.split_at(2);
+            game.play_move(Move::new(pos(src), pos(dst))).unwrap();
+        }
+    }
+
+    #[test]
+    fn kingside_castling_moves_the_rook_and_is_reported() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "f8c5"]);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let seen = events.clone();
+        game.add_observer(move |_: &ChessGame, event: &GameEvent| seen.borrow_mut().push(*event));
+
+        play(&mut game, &["e1g1"]);
+
+        assert_eq!(
+            game.get_field(pos("g1")),
+            Some(Piece::White(PieceType::King))
+        );
+        assert_eq!(
+            game.get_field(pos("f1")),
+            Some(Piece::White(PieceType::Rook))
+        );
+        assert_eq!(game.get_field(pos("h1")), None);
+        assert!(events.borrow().contains(&GameEvent::Castling {
+            mv: Move::new(pos("e1"), pos("g1")),
+            rook: Move::new(pos("h1"), pos("f1")),
+        }));
+        assert_eq!(
+            game.history().last().unwrap().castling,
+            Some(CastlingSide::Kingside)
+        );
+        for side in CastlingSide::BOTH {
+            assert!(!game.castling_rights().has(Turn::WhitePlays, side));
+            assert!(game.castling_rights().has(Turn::BlackPlays, side));
+        }
+    }
+
+    #[test]
+    fn moving_a_rook_loses_its_side_and_undo_gives_it_back() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["a2a4", "e7e5", "a1a3"]);
+        let rights = game.castling_rights();
+        assert!(!rights.has(Turn::WhitePlays, CastlingSide::Queenside));
+        assert!(rights.has(Turn::WhitePlays, CastlingSide::Kingside));
+
+        game.undo_move().unwrap();
+        assert!(game
+            .castling_rights()
+            .has(Turn::WhitePlays, CastlingSide::Queenside));
+    }
+
+    #[test]
+    fn the_king_does_not_castle_out_of_through_or_into_check() {
+        let mut tiles = vec![vec![None; 8]; 8];
+        tiles[0][4] = Some(Piece::White(PieceType::King));
+        tiles[0][0] = Some(Piece::White(PieceType::Rook));
+        tiles[0][7] = Some(Piece::White(PieceType::Rook));
+        tiles[7][4] = Some(Piece::Black(PieceType::King));
+        // attacks f1, the king would pass through it
+        tiles[7][5] = Some(Piece::Black(PieceType::Rook));
+        let mut game = ChessGame::from_tiles(tiles, Turn::WhitePlays).unwrap();
+        game.set_castling_rights(CastlingRights::all());
+
+        let moves = game.legal_moves();
+        assert!(!moves.contains(&Move::new(pos("e1"), pos("g1"))));
+        assert!(moves.contains(&Move::new(pos("e1"), pos("c1"))));
+
+        // b1 is attacked, but only the rook passes it
+        game.tiles[7][5] = None;
+        game.tiles[7][1] = Some(Piece::Black(PieceType::Rook));
+        let moves = game.legal_moves();
+        assert!(moves.contains(&Move::new(pos("e1"), pos("g1"))));
+        assert!(moves.contains(&Move::new(pos("e1"), pos("c1"))));
+
+        game.tiles[7][1] = None;
+        game.tiles[7][2] = Some(Piece::Black(PieceType::Rook));
+        assert!(!game
+            .legal_moves()
+            .contains(&Move::new(pos("e1"), pos("c1"))));
+    }
+
+    #[test]
+    fn capablanca_king_castles_by_three_files() {
+        let mut tiles = vec![vec![None; 10]; 8];
+        tiles[0][5] = Some(Piece::White(PieceType::King));
+        tiles[0][9] = Some(Piece::White(PieceType::Rook));
+        tiles[7][5] = Some(Piece::Black(PieceType::King));
+        let mut game = ChessGame::from_tiles(tiles, Turn::WhitePlays).unwrap();
+        game.set_castling_rights(CastlingRights::all());
+
+        play(&mut game, &["f1i1"]);
+        assert_eq!(
+            game.get_field(pos("h1")),
+            Some(Piece::White(PieceType::Rook))
+        );
+    }
+
+    #[test]
+    fn narrow_boards_have_no_castling() {
+        let game = ChessGame::from_fen("K w - - 0 1").unwrap();
+        assert!(game.legal_moves().is_empty());
+
+        // the rights alone do not make room for the king and the rook
+        let mut game = ChessGame::from_fen("2k/3/R1K w - - 0 1").unwrap();
+        game.set_castling_rights(CastlingRights::all());
+        let moves = game.legal_moves();
+        assert!(!moves.is_empty());
+        assert!(moves
+            .iter()
+            .all(|mv| castling_side(&game.tiles, mv).is_none()));
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt;
+
+use crate::movegen::Move;
+use crate::solution::{ChessGame, GameOutcome, Piece, Turn};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    MoveMade { mv: Move, piece: Piece },
+    Capture { mv: Move, captured: Piece },
+    // piece is the one the pawn was promoted to
+    Promotion { mv: Move, piece: Piece },
+    // mv is the move of the king, rook the jump of the rook over it
+    Castling { mv: Move, rook: Move },
+    Check { color: Turn },
+    Undo { mv: Move },
+    DrawOffered { color: Turn },
+    DrawDeclined { color: Turn },
+    GameEnd { outcome: GameOutcome },
+}
+
+// events of one move are delivered in the order they are listed in GameEvent,
+// the game is already in the state after the move
+pub trait GameObserver {
+    fn notify(&mut self, game: &ChessGame, event: &GameEvent);
+}
+
+impl<F: FnMut(&ChessGame, &GameEvent)> GameObserver for F {
+    fn notify(&mut self, game: &ChessGame, event: &GameEvent) {
+        self(game, event)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ObserverId(usize);
+
+#[derive(Default)]
+pub(crate) struct Observers {
+    list: Vec<(ObserverId, Box<dyn GameObserver>)>,
+    next_id: usize,
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Observers")
+            .field("count", &self.list.len())
+            .finish()
+    }
+}
+
+impl Observers {
+    fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+}
+
+impl ChessGame {
+    pub fn add_observer<O: GameObserver + 'static>(&mut self, observer: O) -> ObserverId {
+        let id = ObserverId(self.observers.next_id);
+        self.observers.next_id += 1;
+        self.observers.list.push((id, Box::new(observer)));
+
+        id
+    }
+    pub fn remove_observer(&mut self, id: ObserverId) -> bool {
+        let count = self.observers.list.len();
+        self.observers
+            .list
+            .retain(|(observer_id, _)| *observer_id != id);
+
+        self.observers.list.len() != count
+    }
+
+    pub(crate) fn notify_observers(&mut self, events: &[GameEvent]) {
+        if self.observers.is_empty() {
+            return;
+        }
+
+        // observers get the game borrowed, so they are taken out for the time of the call
+        let mut observers = std::mem::take(&mut self.observers);
+        for event in events {
+            for (_, observer) in observers.list.iter_mut() {
+                observer.notify(self, event);
+            }
+        }
+
+        self.observers = observers;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::solution::{GameResult, PieceType, Position, Termination};
+
+    fn pos(square: &str) -> Position {
+        Position::try_from(square).unwrap()
+    }
+
+    fn mv(text: &str) -> Move {
+        let (src, dst) = text.split_at(2);
+        Move::new(pos(src), pos(dst))
+    }
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for text in moves {
+            game.play_move(mv(text)).unwrap();
+        }
+    }
+
+    fn record(game: &mut ChessGame) -> (ObserverId, Rc<RefCell<Vec<GameEvent>>>) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let seen = events.clone();
+        let id = game
+            .add_observer(move |_: &ChessGame, event: &GameEvent| seen.borrow_mut().push(*event));
+        (id, events)
+    }
+
+    #[test]
+    fn a_capture_follows_the_move() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["e2e4", "d7d5"]);
+        let (_, events) = record(&mut game);
+
+        play(&mut game, &["e4d5"]);
+        assert_eq!(
+            *events.borrow(),
+            [
+                GameEvent::MoveMade {
+                    mv: mv("e4d5"),
+                    piece: Piece::White(PieceType::Pawn),
+                },
+                GameEvent::Capture {
+                    mv: mv("e4d5"),
+                    captured: Piece::Black(PieceType::Pawn),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn promotion_and_check() {
+        let mut game = ChessGame::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        let (_, events) = record(&mut game);
+        let promotion = Move::with_promotion(pos("a7"), pos("a8"), PieceType::Queen);
+
+        game.play_move(promotion).unwrap();
+        assert_eq!(
+            *events.borrow(),
+            [
+                GameEvent::MoveMade {
+                    mv: promotion,
+                    piece: Piece::White(PieceType::Pawn),
+                },
+                GameEvent::Promotion {
+                    mv: promotion,
+                    piece: Piece::White(PieceType::Queen),
+                },
+                GameEvent::Check {
+                    color: Turn::BlackPlays,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn undo_is_reported() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["e2e4"]);
+        let (_, events) = record(&mut game);
+
+        game.undo_move().unwrap();
+        assert_eq!(*events.borrow(), [GameEvent::Undo { mv: mv("e2e4") }]);
+    }
+
+    #[test]
+    fn the_game_ends_after_the_mating_move() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["f2f3", "e7e5", "g2g4"]);
+        let (_, events) = record(&mut game);
+
+        play(&mut game, &["d8h4"]);
+        assert_eq!(
+            *events.borrow(),
+            [
+                GameEvent::MoveMade {
+                    mv: mv("d8h4"),
+                    piece: Piece::Black(PieceType::Queen),
+                },
+                GameEvent::Check {
+                    color: Turn::WhitePlays,
+                },
+                GameEvent::GameEnd {
+                    outcome: GameOutcome {
+                        result: GameResult::BlackWins,
+                        termination: Termination::Checkmate,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn every_observer_is_notified_until_removed() {
+        let mut game = ChessGame::new_game();
+        let (first, first_events) = record(&mut game);
+        let (_, second_events) = record(&mut game);
+
+        play(&mut game, &["e2e4"]);
+        assert_eq!(first_events.borrow().len(), 1);
+        assert_eq!(*first_events.borrow(), *second_events.borrow());
+
+        assert!(game.remove_observer(first));
+        assert!(!game.remove_observer(first));
+        play(&mut game, &["e7e5"]);
+        assert_eq!(first_events.borrow().len(), 1);
+        assert_eq!(second_events.borrow().len(), 2);
+    }
+}
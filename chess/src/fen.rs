@@ -0,0 +1,443 @@
+use core::convert::TryFrom;
+use std::rc::Rc;
+
+use crate::board::BoardSetup;
+use crate::castling::{CastlingRights, CastlingSide};
+use crate::solution::{ChessGame, Error, Piece, PieceType, Position, Turn, MAX_TILES_SIZE};
+use crate::variants::{self, Rules, ThreeCheck, THREE_CHECK_LIMIT};
+
+// Forsyth-Edwards Notation of a position, e.g.
+// rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+// Ranks may have more than 8 squares and empty runs of more than 9, the
+// archbishop is A and the chancellor C. Variants add their own fields after
+// the six standard ones: Three-check the checks each side still has to give
+// as +white+black, and every variant its name, e.g.
+// rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +3+3 threecheck
+// The en passant field is the square skipped by a double pawn push of the
+// last move, or "-".
+
+// counters of the position the game started from, the history continues them
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct StartCounters {
+    // moves since the last capture or pawn move
+    pub(crate) halfmove_clock: usize,
+    pub(crate) fullmove_number: usize,
+    // checks given by white and black, for Three-check
+    pub(crate) checks: [usize; 2],
+}
+
+impl Default for StartCounters {
+    fn default() -> Self {
+        StartCounters {
+            halfmove_clock: 0,
+            fullmove_number: 1,
+            checks: [0, 0],
+        }
+    }
+}
+
+impl StartCounters {
+    pub(crate) fn checks(&self, color: Turn) -> usize {
+        match color {
+            Turn::WhitePlays => self.checks[0],
+            Turn::BlackPlays => self.checks[1],
+        }
+    }
+}
+
+fn piece_char(piece: Piece) -> char {
+    let c = match piece.piece_type() {
+        PieceType::Pawn => 'p',
+        PieceType::Knight => 'n',
+        PieceType::Bishop => 'b',
+        PieceType::Rook => 'r',
+        PieceType::Queen => 'q',
+        PieceType::King => 'k',
+        PieceType::Archbishop => 'a',
+        PieceType::Chancellor => 'c',
+    };
+    match piece.color() {
+        Turn::WhitePlays => c.to_ascii_uppercase(),
+        Turn::BlackPlays => c,
+    }
+}
+
+fn char_piece(c: char) -> Option<Piece> {
+    let piece_type = match c.to_ascii_lowercase() {
+        'p' => PieceType::Pawn,
+        'n' => PieceType::Knight,
+        'b' => PieceType::Bishop,
+        'r' => PieceType::Rook,
+        'q' => PieceType::Queen,
+        'k' => PieceType::King,
+        'a' => PieceType::Archbishop,
+        'c' => PieceType::Chancellor,
+        _ => return None,
+    };
+    let color = if c.is_ascii_uppercase() {
+        Turn::WhitePlays
+    } else {
+        Turn::BlackPlays
+    };
+
+    Some(Piece::new(color, piece_type))
+}
+
+impl ChessGame {
+    pub fn to_fen(&self) -> String {
+        let mut fields = Vec::new();
+
+        let ranks: Vec<String> = self
+            .tiles
+            .iter()
+            .rev()
+            .map(|row| {
+                let mut rank = String::new();
+                let mut empty = 0;
+                for field in row {
+                    match field {
+                        None => empty += 1,
+                        Some(piece) => {
+                            if empty > 0 {
+                                rank.push_str(&empty.to_string());
+                                empty = 0;
+                            }
+                            rank.push(piece_char(*piece));
+                        }
+                    }
+                }
+                if empty > 0 {
+                    rank.push_str(&empty.to_string());
+                }
+                rank
+            })
+            .collect();
+        fields.push(ranks.join("/"));
+
+        fields.push(
+            match self.current_player() {
+                Turn::WhitePlays => "w",
+                Turn::BlackPlays => "b",
+            }
+            .to_string(),
+        );
+
+        let mut castling = String::new();
+        for (color, side, c) in castling_letters() {
+            if self.castling_rights().has(color, side) {
+                castling.push(c);
+            }
+        }
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        fields.push(castling);
+        fields.push(match self.en_passant_target() {
+            Some(pos) => pos.to_string(),
+            None => "-".to_string(),
+        });
+
+        let history = self.history();
+        let halfmove_clock = match history.iter().rev().position(|played| {
+            played.captured.is_some() || played.piece.piece_type() == PieceType::Pawn
+        }) {
+            Some(moves) => moves,
+            None => self.start.halfmove_clock + history.len(),
+        };
+        let black_moves = history
+            .iter()
+            .filter(|played| played.piece.color() == Turn::BlackPlays)
+            .count();
+        fields.push(halfmove_clock.to_string());
+        fields.push((self.start.fullmove_number + black_moves).to_string());
+
+        let name = self.rules().name();
+        if name == ThreeCheck.name() {
+            let remaining = |color| {
+                THREE_CHECK_LIMIT
+                    .saturating_sub(self.checks_given(color))
+                    .to_string()
+            };
+            fields.push(format!(
+                "+{}+{}",
+                remaining(Turn::WhitePlays),
+                remaining(Turn::BlackPlays)
+            ));
+        }
+        if name != "Standard" {
+            fields.push(variants::name_key(name));
+        }
+
+        fields.join(" ")
+    }
+
+    // 8x8, 10x8 and 10x10 boards get the promotions and pawn rows of their
+    // setups, other sizes the standard promotions
+    pub fn from_fen(fen: &str) -> Result<ChessGame, Error> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 6 {
+            return Err(Error::InvalidFen);
+        }
+
+        let mut tiles = Vec::new();
+        for rank in fields[0].split('/') {
+            let mut row = Vec::new();
+            let mut empty = String::new();
+            for c in rank.chars() {
+                if c.is_ascii_digit() {
+                    empty.push(c);
+                    continue;
+                }
+                skip_empty(&mut row, &empty)?;
+                empty.clear();
+                row.push(Some(char_piece(c).ok_or(Error::InvalidFen)?));
+            }
+            skip_empty(&mut row, &empty)?;
+            tiles.push(row);
+        }
+        tiles.reverse();
+        let (width, height) = (tiles[0].len(), tiles.len());
+        if width == 0
+            || width > MAX_TILES_SIZE
+            || height > MAX_TILES_SIZE
+            || tiles.iter().any(|row| row.len() != width)
+        {
+            return Err(Error::InvalidFen);
+        }
+
+        let next_turn = match fields[1] {
+            "w" => Turn::WhitePlays,
+            "b" => Turn::BlackPlays,
+            _ => return Err(Error::InvalidFen),
+        };
+
+        let mut castling = CastlingRights::none();
+        if fields[2] != "-" {
+            for c in fields[2].chars() {
+                let (color, side, _) = castling_letters()
+                    .into_iter()
+                    .find(|(_, _, letter)| *letter == c)
+                    .ok_or(Error::InvalidFen)?;
+                castling.set(color, side, true);
+            }
+        }
+
+        let en_passant = match fields[3] {
+            "-" => None,
+            square => {
+                let pos = Position::try_from(square).map_err(|_| Error::InvalidFen)?;
+                if pos.get_x() >= width || pos.get_y() >= height {
+                    return Err(Error::InvalidFen);
+                }
+                Some(pos)
+            }
+        };
+
+        let halfmove_clock = parse_number(fields[4])?;
+        let fullmove_number = parse_number(fields[5])?;
+        if fullmove_number == 0 {
+            return Err(Error::InvalidFen);
+        }
+
+        // the checks field and the variant, in this order, both optional
+        let mut extra = &fields[6..];
+        let mut remaining_checks = None;
+        if let Some(checks) = extra.first().and_then(|field| field.strip_prefix('+')) {
+            let (white, black) = checks.split_once('+').ok_or(Error::InvalidFen)?;
+            let remaining = [parse_number(white)?, parse_number(black)?];
+            if remaining.iter().any(|left| *left > THREE_CHECK_LIMIT) {
+                return Err(Error::InvalidFen);
+            }
+            remaining_checks = Some(remaining);
+            extra = &extra[1..];
+        }
+        let rules = match extra {
+            [] if remaining_checks.is_some() => Rc::new(ThreeCheck),
+            [] => variants::rules_by_name("Standard").unwrap(),
+            [tag] => variants::rules_by_name(tag).ok_or(Error::InvalidFen)?,
+            _ => return Err(Error::InvalidFen),
+        };
+        let three_check = rules.name() == ThreeCheck.name();
+        if remaining_checks.is_some() != three_check {
+            return Err(Error::InvalidFen);
+        }
+
+        let mut game = ChessGame::from_tiles(tiles, next_turn)?;
+        game.rules = rules;
+        game.set_castling_rights(castling);
+        game.en_passant = en_passant;
+        if let Some(setup) = BoardSetup::for_size(width, height) {
+            game.setup = Rc::new(setup);
+        }
+        game.start = StartCounters {
+            halfmove_clock,
+            fullmove_number,
+            checks: remaining_checks.map_or([0, 0], |remaining| {
+                remaining.map(|left| THREE_CHECK_LIMIT - left)
+            }),
+        };
+
+        Ok(game)
+    }
+}
+
+fn castling_letters() -> [(Turn, CastlingSide, char); 4] {
+    [
+        (Turn::WhitePlays, CastlingSide::Kingside, 'K'),
+        (Turn::WhitePlays, CastlingSide::Queenside, 'Q'),
+        (Turn::BlackPlays, CastlingSide::Kingside, 'k'),
+        (Turn::BlackPlays, CastlingSide::Queenside, 'q'),
+    ]
+}
+
+// adds the run of empty squares, if there is one
+fn skip_empty(row: &mut Vec<Option<Piece>>, run: &str) -> Result<(), Error> {
+    if run.is_empty() {
+        return Ok(());
+    }
+
+    let len = row.len() + parse_number(run)?;
+    if len > MAX_TILES_SIZE {
+        return Err(Error::InvalidFen);
+    }
+    row.resize(len, None);
+
+    Ok(())
+}
+
+fn parse_number(text: &str) -> Result<usize, Error> {
+    text.parse().map_err(|_| Error::InvalidFen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movegen::Move;
+    use crate::solution::{GameResult, Termination};
+    use crate::variants::{Atomic, KingOfTheHill};
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for mv in moves {
+            let (src, dst) = mv.split_at(2);
+            let mv = Move::new(
+                Position::try_from(src).unwrap(),
+                Position::try_from(dst).unwrap(),
+            );
+            game.play_move(mv).unwrap();
+        }
+    }
+
+    fn round_trip(game: &ChessGame) -> ChessGame {
+        let fen = game.to_fen();
+        let parsed = ChessGame::from_fen(&fen).unwrap();
+        assert_eq!(parsed.to_fen(), fen);
+        assert_eq!(parsed.rules().name(), game.rules().name());
+        assert_eq!(parsed.legal_moves(), game.legal_moves());
+        parsed
+    }
+
+    #[test]
+    fn starting_positions() {
+        assert_eq!(ChessGame::new_game().to_fen(), START);
+        assert_eq!(
+            ChessGame::from_setup(BoardSetup::capablanca()).to_fen(),
+            "rnabqkbcnr/pppppppppp/10/10/10/10/PPPPPPPPPP/RNABQKBCNR w KQkq - 0 1"
+        );
+        let game = ChessGame::from_fen(START).unwrap();
+        assert_eq!(game.tiles, ChessGame::new_game().tiles);
+        assert_eq!(game.legal_moves().len(), 20);
+    }
+
+    #[test]
+    fn move_counters_continue_from_the_fen() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["g1f3", "g8f6", "f3g1"]);
+        assert_eq!(
+            game.to_fen(),
+            "rnbqkb1r/pppppppp/5n2/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 3 2"
+        );
+
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/4K2R b K - 10 20").unwrap();
+        play(&mut game, &["e8d7", "h1h2"]);
+        assert_eq!(game.to_fen(), "8/3k4/8/8/8/8/7R/4K3 b - - 12 21");
+    }
+
+    #[test]
+    fn en_passant_square() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["e2e4"]);
+        assert_eq!(
+            round_trip(&game).to_fen(),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+        );
+        play(&mut game, &["g8f6"]);
+        assert!(game.to_fen().contains(" KQkq - "));
+
+        let game = ChessGame::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2").unwrap();
+        assert_eq!(
+            game.en_passant_target(),
+            Some(Position::try_from("d6").unwrap())
+        );
+        assert!(game.legal_moves().contains(&Move::new(
+            Position::try_from("e5").unwrap(),
+            Position::try_from("d6").unwrap()
+        )));
+    }
+
+    #[test]
+    fn variants_round_trip() {
+        let mut game = ChessGame::new_game().with_rules(KingOfTheHill);
+        play(&mut game, &["e2e4", "e7e5", "e1e2"]);
+        let fen = round_trip(&game).to_fen();
+        assert!(fen.ends_with(" b kq - 1 2 kingofthehill"), "{}", fen);
+
+        let mut game = ChessGame::new_game().with_rules(ThreeCheck);
+        play(&mut game, &["e2e4", "f7f6", "d1h5"]);
+        let parsed = round_trip(&game);
+        assert!(parsed.to_fen().ends_with(" +2+3 threecheck"));
+        assert_eq!(parsed.checks_given(Turn::WhitePlays), 1);
+
+        let mut game = ChessGame::new_game().with_rules(Atomic);
+        play(&mut game, &["g1f3", "d7d5", "f3e5", "d8d6", "e5f7"]);
+        let fen = round_trip(&game).to_fen();
+        assert!(fen.ends_with(" atomic"), "{}", fen);
+    }
+
+    #[test]
+    fn remaining_checks_count_towards_three_check() {
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1 +1+3").unwrap();
+        assert_eq!(game.rules().name(), "Three-check");
+        play(&mut game, &["h1h8"]);
+        let outcome = game.outcome().unwrap();
+        assert_eq!(outcome.result, GameResult::WhiteWins);
+        assert_eq!(outcome.termination, Termination::ThreeChecks);
+    }
+
+    #[test]
+    fn invalid_fens_are_rejected() {
+        for fen in [
+            "",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNX w KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +4+3",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 +3+3 atomic",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 crazyhouse",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1",
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq 3e 0 1",
+            "99999999999/8 w - - 0 1",
+        ] {
+            assert_eq!(
+                ChessGame::from_fen(fen).err(),
+                Some(Error::InvalidFen),
+                "{}",
+                fen
+            );
+        }
+    }
+}
@@ -0,0 +1,13 @@
+pub mod board;
+pub mod castling;
+pub mod events;
+pub mod fen;
+pub mod movegen;
+pub mod pgn;
+pub mod see;
+pub mod solution;
+pub mod solver;
+pub mod square;
+pub mod stats;
+pub mod time_control;
+pub mod variants;
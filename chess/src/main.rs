@@ -1,17 +1,11 @@
-mod solution;
-
-use solution::ChessGame;
-use solution::Error;
-use solution::Piece::*;
-use solution::PieceType::*;
-use solution::Turn;
-use solution::Position;
+use chess::solution::ChessGame;
+use chess::solution::Error;
 
 
 fn main() -> Result<(), Error> {
     // println!("Program works!");
 
-    let mut game = ChessGame::new_game();
+    let game = ChessGame::new_game();
 
     // let column = "a";
     // let row = 1;
@@ -64,21 +58,8 @@ fn main() -> Result<(), Error> {
 
     // println!("Make resolution: {}", text);
 
-    for i in 1..solution::TILES_SIZE + 1 {
-        //println!("In row {}", i);
-        for col in solution::VALID_COLUMNS {
-            let pos_str = format!("{}{}", col, i);
-            // println!("Formatting: {}", pos_str);
-            let pos: Position = pos_str.as_str().try_into()?;
-            println!("{}{} -> {:?}", col, i, game.get_field(pos));
-        }
-
-        // println!("{}", solution::VALID_COLUMNS
-        //     .map(|col| game.get_field(
-        //         format!("{}{}", col, i).as_str().try_into().unwrap()
-        //     ))
-        //     .join("<->")
-        // );
+    for pos in game.squares() {
+        println!("{} -> {:?}", pos, game.get_field(pos));
     }
 
     // println!();
@@ -0,0 +1,494 @@
+use crate::board::BoardSetup;
+use crate::castling::{self, CastlingSide};
+use crate::solution::{ChessGame, Error, Piece, PieceType, Position, Tiles, Turn};
+use crate::square::Direction;
+
+// Piece movement rules of standard chess on top of ChessGame,
+// including the fairy pieces of the bigger boards.
+// En passant is generated from the target square the game keeps after
+// a double pawn push.
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub src: Position,
+    pub dst: Position,
+    pub promotion: Option<PieceType>,
+}
+
+impl Move {
+    pub fn new(src: Position, dst: Position) -> Self {
+        Move {
+            src,
+            dst,
+            promotion: None,
+        }
+    }
+    pub fn with_promotion(src: Position, dst: Position, piece_type: PieceType) -> Self {
+        Move {
+            src,
+            dst,
+            promotion: Some(piece_type),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PlayedMove {
+    pub mv: Move,
+    // before promotion
+    pub piece: Piece,
+    pub captured: Option<Piece>,
+    pub castling: Option<CastlingSide>,
+    // the move gave check
+    pub check: bool,
+}
+
+const KNIGHT_OFFSETS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+// pieces moving like a knight, rook or bishop
+const KNIGHT_MOVERS: [PieceType; 3] = [
+    PieceType::Knight,
+    PieceType::Archbishop,
+    PieceType::Chancellor,
+];
+const ROOK_MOVERS: [PieceType; 3] = [PieceType::Rook, PieceType::Queen, PieceType::Chancellor];
+const BISHOP_MOVERS: [PieceType; 3] = [PieceType::Bishop, PieceType::Queen, PieceType::Archbishop];
+
+impl ChessGame {
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.rules().legal_moves(self)
+    }
+    // is the player on turn in check
+    pub fn is_check(&self) -> bool {
+        self.rules().in_check(&self.tiles, self.current_player())
+    }
+    pub fn is_checkmate(&self) -> bool {
+        self.is_check() && self.legal_moves().is_empty()
+    }
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_check() && self.legal_moves().is_empty()
+    }
+    pub fn is_attacked(&self, pos: Position, by: Turn) -> bool {
+        is_attacked(&self.tiles, &pos, by)
+    }
+    pub fn attackers(&self, pos: Position, by: Turn) -> Vec<Position> {
+        attackers(&self.tiles, &pos, by)
+    }
+
+    // same as make_move, but the move has to follow the rules of chess
+    pub fn play_move(&mut self, mv: Move) -> Result<Option<Piece>, Error> {
+        self.start_move()?;
+
+        if !self.legal_moves().contains(&mv) {
+            return Err(Error::InvalidMove);
+        }
+
+        let piece = field(&self.tiles, &mv.src).ok_or(Error::InvalidMove)?;
+        let before = self.undo_state();
+        let captured = self.move_pieces(&mv, piece);
+        self.finish_move(mv, piece, captured, before);
+
+        Ok(captured)
+    }
+
+    // no validation, the move has to come from legal_moves
+    pub(crate) fn apply_move(&mut self, mv: &Move) -> Option<Piece> {
+        let piece = field(&self.tiles, &mv.src)?;
+        let captured = self.move_pieces(mv, piece);
+        self.update_turn();
+
+        captured
+    }
+
+    // moves the pieces by the rules of the game and updates the castling rights
+    // and the en passant target
+    pub(crate) fn move_pieces(&mut self, mv: &Move, piece: Piece) -> Option<Piece> {
+        let captured = self.rules.apply_move(&mut self.tiles, mv);
+        self.castling.update(&self.tiles, mv, piece);
+        self.en_passant = en_passant_target(mv, piece);
+
+        captured
+    }
+}
+
+fn field(tiles: &Tiles, pos: &Position) -> Option<Piece> {
+    tiles[pos.get_y()][pos.get_x()]
+}
+
+// None when the position would leave the board
+pub(crate) fn offset(tiles: &Tiles, pos: &Position, dx: i8, dy: i8) -> Option<Position> {
+    pos.offset(dx, dy)
+        .filter(|next| next.get_y() < tiles.len() && next.get_x() < tiles[0].len())
+}
+
+pub(crate) fn step(tiles: &Tiles, pos: &Position, direction: Direction) -> Option<Position> {
+    let (dx, dy) = direction.delta();
+    offset(tiles, pos, dx, dy)
+}
+
+fn pawn_direction(color: Turn) -> i8 {
+    match color {
+        Turn::WhitePlays => 1,
+        Turn::BlackPlays => -1,
+    }
+}
+
+fn pawn_start_row(setup: &BoardSetup, color: Turn) -> usize {
+    match color {
+        Turn::WhitePlays => setup.pawn_row(),
+        Turn::BlackPlays => setup.height() - 1 - setup.pawn_row(),
+    }
+}
+
+pub(crate) fn promotion_row(setup: &BoardSetup, color: Turn) -> usize {
+    match color {
+        Turn::WhitePlays => setup.height() - 1,
+        Turn::BlackPlays => 0,
+    }
+}
+
+// square skipped by a double pawn push
+fn en_passant_target(mv: &Move, piece: Piece) -> Option<Position> {
+    let (_, dy) = mv.dst.distance_from(&mv.src);
+    if piece.piece_type() == PieceType::Pawn && dy.abs() == 2 {
+        Some(Position {
+            x: mv.src.get_x(),
+            y: (mv.src.get_y() + mv.dst.get_y()) / 2,
+        })
+    } else {
+        None
+    }
+}
+
+// pawn taken en passant, a pawn moving diagonally to an empty square
+// captures the pawn next to it
+pub(crate) fn en_passant_capture(tiles: &Tiles, mv: &Move) -> Option<Position> {
+    let is_pawn = field(tiles, &mv.src).map(|piece| piece.piece_type()) == Some(PieceType::Pawn);
+    if is_pawn && mv.src.get_x() != mv.dst.get_x() && field(tiles, &mv.dst).is_none() {
+        Some(Position {
+            x: mv.dst.get_x(),
+            y: mv.src.get_y(),
+        })
+    } else {
+        None
+    }
+}
+
+pub(crate) fn apply_to_tiles(tiles: &mut Tiles, mv: &Move) -> Option<Piece> {
+    // the rook jumps over the castling king
+    if let Some((rook_src, rook_dst)) = castling::rook_move(tiles, mv) {
+        tiles[rook_dst.get_y()][rook_dst.get_x()] = field(tiles, &rook_src);
+        tiles[rook_src.get_y()][rook_src.get_x()] = None;
+    }
+
+    let captured = match en_passant_capture(tiles, mv) {
+        Some(pos) => tiles[pos.get_y()][pos.get_x()].take(),
+        None => field(tiles, &mv.dst),
+    };
+    let moving_piece = field(tiles, &mv.src).map(|piece| match mv.promotion {
+        Some(piece_type) => Piece::new(piece.color(), piece_type),
+        None => piece,
+    });
+
+    tiles[mv.dst.get_y()][mv.dst.get_x()] = moving_piece;
+    tiles[mv.src.get_y()][mv.src.get_x()] = None;
+
+    captured
+}
+
+pub(crate) fn find_king(tiles: &Tiles, color: Turn) -> Option<Position> {
+    let king = Piece::new(color, PieceType::King);
+    tiles.iter().enumerate().find_map(|(y, row)| {
+        row.iter()
+            .position(|field| *field == Some(king))
+            .map(|x| Position { x, y })
+    })
+}
+
+// a side without a king is never in check
+pub(crate) fn in_check(tiles: &Tiles, color: Turn) -> bool {
+    match find_king(tiles, color) {
+        Some(king_pos) => is_attacked(tiles, &king_pos, color.opponent()),
+        None => false,
+    }
+}
+
+fn is_attacked(tiles: &Tiles, pos: &Position, by: Turn) -> bool {
+    !attackers(tiles, pos, by).is_empty()
+}
+
+// pieces of the given color attacking the position, pieces behind other
+// pieces on the same line are not included
+pub(crate) fn attackers(tiles: &Tiles, pos: &Position, by: Turn) -> Vec<Position> {
+    let mut found = Vec::new();
+    let mut check = |from: Option<Position>, piece_types: &[PieceType]| {
+        if let Some(from) = from {
+            if let Some(piece) = field(tiles, &from) {
+                if piece.color() == by && piece_types.contains(&piece.piece_type()) {
+                    found.push(from);
+                }
+            }
+        }
+    };
+
+    // pawns attack towards the opponent, so look in the opposite direction
+    let pawn_dy = -pawn_direction(by);
+    for dx in [-1, 1] {
+        check(offset(tiles, pos, dx, pawn_dy), &[PieceType::Pawn]);
+    }
+    for (dx, dy) in KNIGHT_OFFSETS {
+        check(offset(tiles, pos, dx, dy), &KNIGHT_MOVERS);
+    }
+    for direction in Direction::ALL {
+        check(step(tiles, pos, direction), &[PieceType::King]);
+    }
+
+    // first piece along each line
+    let first_on_line = |direction: Direction| {
+        let mut current = step(tiles, pos, direction);
+        while let Some(from) = current {
+            if field(tiles, &from).is_some() {
+                break;
+            }
+            current = step(tiles, &from, direction);
+        }
+        current
+    };
+    for direction in Direction::ORTHOGONAL {
+        check(first_on_line(direction), &ROOK_MOVERS);
+    }
+    for direction in Direction::DIAGONAL {
+        check(first_on_line(direction), &BISHOP_MOVERS);
+    }
+
+    found
+}
+
+// moves following the piece movement rules, the own king may be left in check
+// en_passant is the square a pawn may capture on after a double push
+pub(crate) fn pseudo_legal_moves(
+    tiles: &Tiles,
+    color: Turn,
+    setup: &BoardSetup,
+    en_passant: Option<Position>,
+) -> Vec<Move> {
+    let mut moves = Vec::new();
+
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, field) in row.iter().enumerate() {
+            let src = Position { x, y };
+            let piece = match field {
+                Some(piece) if piece.color() == color => piece,
+                _ => continue,
+            };
+
+            match piece.piece_type() {
+                PieceType::Pawn => pawn_moves(tiles, &src, color, setup, en_passant, &mut moves),
+                PieceType::Knight => step_moves(tiles, &src, color, &KNIGHT_OFFSETS, &mut moves),
+                PieceType::King => {
+                    let king_offsets = Direction::ALL.map(|direction| direction.delta());
+                    step_moves(tiles, &src, color, &king_offsets, &mut moves)
+                }
+                PieceType::Rook => {
+                    slide_moves(tiles, &src, color, &Direction::ORTHOGONAL, &mut moves)
+                }
+                PieceType::Bishop => {
+                    slide_moves(tiles, &src, color, &Direction::DIAGONAL, &mut moves)
+                }
+                PieceType::Queen => {
+                    slide_moves(tiles, &src, color, &Direction::ORTHOGONAL, &mut moves);
+                    slide_moves(tiles, &src, color, &Direction::DIAGONAL, &mut moves);
+                }
+                PieceType::Archbishop => {
+                    step_moves(tiles, &src, color, &KNIGHT_OFFSETS, &mut moves);
+                    slide_moves(tiles, &src, color, &Direction::DIAGONAL, &mut moves);
+                }
+                PieceType::Chancellor => {
+                    step_moves(tiles, &src, color, &KNIGHT_OFFSETS, &mut moves);
+                    slide_moves(tiles, &src, color, &Direction::ORTHOGONAL, &mut moves);
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+fn step_moves(
+    tiles: &Tiles,
+    src: &Position,
+    color: Turn,
+    offsets: &[(i8, i8)],
+    moves: &mut Vec<Move>,
+) {
+    for &(dx, dy) in offsets {
+        if let Some(dst) = offset(tiles, src, dx, dy) {
+            match field(tiles, &dst) {
+                Some(piece) if piece.color() == color => (),
+                _ => moves.push(Move::new(*src, dst)),
+            }
+        }
+    }
+}
+
+fn slide_moves(
+    tiles: &Tiles,
+    src: &Position,
+    color: Turn,
+    directions: &[Direction],
+    moves: &mut Vec<Move>,
+) {
+    for &direction in directions {
+        let mut current = step(tiles, src, direction);
+        while let Some(dst) = current {
+            match field(tiles, &dst) {
+                None => moves.push(Move::new(*src, dst)),
+                Some(piece) => {
+                    if piece.color() != color {
+                        moves.push(Move::new(*src, dst));
+                    }
+                    break;
+                }
+            }
+            current = step(tiles, &dst, direction);
+        }
+    }
+}
+
+fn pawn_moves(
+    tiles: &Tiles,
+    src: &Position,
+    color: Turn,
+    setup: &BoardSetup,
+    en_passant: Option<Position>,
+    moves: &mut Vec<Move>,
+) {
+    let dy = pawn_direction(color);
+
+    let mut push = |dst: Position| {
+        if dst.get_y() == promotion_row(setup, color) {
+            for &piece_type in setup.promotions() {
+                moves.push(Move::with_promotion(*src, dst, piece_type));
+            }
+        } else {
+            moves.push(Move::new(*src, dst));
+        }
+    };
+
+    if let Some(dst) = offset(tiles, src, 0, dy) {
+        if field(tiles, &dst).is_none() {
+            push(dst);
+
+            if src.get_y() == pawn_start_row(setup, color) {
+                if let Some(double_dst) = offset(tiles, &dst, 0, dy) {
+                    if field(tiles, &double_dst).is_none() {
+                        push(double_dst);
+                    }
+                }
+            }
+        }
+    }
+
+    for dx in [-1, 1] {
+        if let Some(dst) = offset(tiles, src, dx, dy) {
+            match field(tiles, &dst) {
+                Some(piece) if piece.color() != color => push(dst),
+                // the pawn that passed has to stand next to this one
+                None if Some(dst) == en_passant => {
+                    let passed = offset(tiles, src, dx, 0).and_then(|pos| field(tiles, &pos));
+                    if passed == Some(Piece::new(color.opponent(), PieceType::Pawn)) {
+                        push(dst);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    // number of move sequences of the given length
+    fn perft(game: &ChessGame, depth: usize) -> usize {
+        let moves = game.legal_moves();
+        if depth == 1 {
+            return moves.len();
+        }
+
+        moves
+            .iter()
+            .map(|mv| {
+                let mut next = game.board_copy();
+                next.apply_move(mv);
+                perft(&next, depth - 1)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn perft_of_the_standard_starting_position() {
+        let game = ChessGame::new_game();
+        let counts: Vec<usize> = (1..=5).map(|depth| perft(&game, depth)).collect();
+        // depth 5 is the first to have en passant captures
+        assert_eq!(counts, [20, 400, 8902, 197281, 4865609]);
+    }
+
+    #[test]
+    fn perft_of_the_capablanca_starting_position() {
+        let game = ChessGame::from_setup(BoardSetup::capablanca());
+        let counts: Vec<usize> = (1..=3).map(|depth| perft(&game, depth)).collect();
+        assert_eq!(counts, [28, 784, 25228]);
+    }
+
+    #[test]
+    fn en_passant_after_a_double_push() {
+        let mut game = ChessGame::from_fen("4k3/8/8/8/2p5/8/1P6/4K3 w - - 0 1").unwrap();
+        let b4 = Move::new(pos("b2"), pos("b4"));
+        let capture = Move::new(pos("c4"), pos("b3"));
+        game.play_move(b4).unwrap();
+        assert_eq!(game.en_passant_target(), Some(pos("b3")));
+        assert!(game.legal_moves().contains(&capture));
+
+        let captured = game.play_move(capture).unwrap();
+        assert_eq!(
+            captured,
+            Some(Piece::new(Turn::WhitePlays, PieceType::Pawn))
+        );
+        assert_eq!(game.get_field(pos("b4")), None);
+        assert_eq!(game.history()[1].captured, captured);
+
+        game.undo_move().unwrap();
+        assert_eq!(
+            game.get_field(pos("b4")),
+            Some(Piece::new(Turn::WhitePlays, PieceType::Pawn))
+        );
+        assert_eq!(game.en_passant_target(), Some(pos("b3")));
+    }
+
+    #[test]
+    fn en_passant_only_right_after_the_double_push() {
+        let mut game = ChessGame::from_fen("4k3/8/8/8/2p5/8/1P6/4K3 w - - 0 1").unwrap();
+        for (src, dst) in [("b2", "b4"), ("e8", "d8"), ("e1", "d1")] {
+            game.play_move(Move::new(pos(src), pos(dst))).unwrap();
+        }
+        assert_eq!(game.en_passant_target(), None);
+        assert!(!game
+            .legal_moves()
+            .contains(&Move::new(pos("c4"), pos("b3"))));
+    }
+
+    fn pos(square: &str) -> Position {
+        Position::try_from(square).unwrap()
+    }
+}
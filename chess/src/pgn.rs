@@ -0,0 +1,227 @@
+use crate::castling::CastlingSide;
+use crate::movegen::{Move, PlayedMove};
+use crate::solution::{ChessGame, PieceType, Termination, Turn};
+
+// Export of a game to PGN, moves are written in SAN.
+// Games that did not start from the standard position, set up by from_tiles,
+// from_fen or on another board, get the SetUp and FEN tags.
+
+const LINE_WIDTH: usize = 80;
+
+// extra tags, e.g. [("White", "Carlsen"), ("Event", "Club championship")],
+// replace the "?" of the seven tag roster, the result is taken from the game
+pub fn to_pgn(game: &ChessGame, tags: &[(&str, &str)]) -> String {
+    let result = game
+        .outcome()
+        .map_or("*", |outcome| outcome.result.to_pgn());
+
+    let mut roster = vec![
+        ("Event", "?"),
+        ("Site", "?"),
+        ("Date", "????.??.??"),
+        ("Round", "?"),
+        ("White", "?"),
+        ("Black", "?"),
+    ];
+    for &(name, value) in tags.iter().filter(|(name, _)| *name != "Result") {
+        match roster
+            .iter_mut()
+            .find(|(roster_name, _)| *roster_name == name)
+        {
+            Some(tag) => tag.1 = value,
+            None => roster.push((name, value)),
+        }
+    }
+    roster.insert(6, ("Result", result));
+
+    if let Some(outcome) = game.outcome() {
+        roster.push(("Termination", outcome.termination.to_pgn()));
+    }
+    if game.rules().name() != "Standard" {
+        roster.push(("Variant", game.rules().name()));
+    }
+    let start = game.starting_position();
+    let fen = start.to_fen();
+    let mut standard = ChessGame::new_game();
+    standard.rules = game.rules.clone();
+    if fen != standard.to_fen() {
+        roster.push(("SetUp", "1"));
+        roster.push(("FEN", &fen));
+    }
+
+    let mut pgn = String::new();
+    for (name, value) in roster {
+        pgn.push_str(&format!("[{} \"{}\"]\n", name, value.replace('"', "\\\"")));
+    }
+    pgn.push('\n');
+
+    let mut tokens = Vec::new();
+    for (index, played) in game.history().iter().enumerate() {
+        let move_number =
+            (index + usize::from(!first_mover_is_white(game))) / 2 + start.start.fullmove_number;
+        if played.piece.color() == Turn::WhitePlays {
+            tokens.push(format!("{}.", move_number));
+        } else if index == 0 {
+            tokens.push(format!("{}...", move_number));
+        }
+
+        let is_mate = index + 1 == game.history().len()
+            && game.outcome().map(|outcome| outcome.termination) == Some(Termination::Checkmate);
+        tokens.push(san(&game.position_before(index), played, is_mate));
+    }
+    if let Some(outcome) = game.outcome() {
+        tokens.push(format!("{{{}}}", outcome.termination));
+    }
+    tokens.push(result.to_string());
+
+    let mut line = String::new();
+    for token in tokens {
+        if !line.is_empty() && line.len() + 1 + token.len() > LINE_WIDTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+
+    pgn
+}
+
+fn first_mover_is_white(game: &ChessGame) -> bool {
+    game.history()
+        .first()
+        .is_none_or(|played| played.piece.color() == Turn::WhitePlays)
+}
+
+fn piece_letter(piece_type: PieceType) -> &'static str {
+    match piece_type {
+        PieceType::Pawn => "",
+        PieceType::Knight => "N",
+        PieceType::Bishop => "B",
+        PieceType::Rook => "R",
+        PieceType::Queen => "Q",
+        PieceType::King => "K",
+        PieceType::Archbishop => "A",
+        PieceType::Chancellor => "C",
+    }
+}
+
+// before is the position the move was played in
+fn san(before: &ChessGame, played: &PlayedMove, is_mate: bool) -> String {
+    let mv = &played.mv;
+    let piece_type = played.piece.piece_type();
+    let mut san = piece_letter(piece_type).to_string();
+
+    if let Some(side) = played.castling {
+        san = match side {
+            CastlingSide::Kingside => "O-O",
+            CastlingSide::Queenside => "O-O-O",
+        }
+        .to_string();
+    } else if piece_type == PieceType::Pawn {
+        if played.captured.is_some() {
+            san.push(mv.src.file().to_char());
+        }
+    } else {
+        // other pieces of the same type that could go to the same square
+        let rivals: Vec<Move> = before
+            .legal_moves()
+            .into_iter()
+            .filter(|other| {
+                other.dst == mv.dst
+                    && other.src != mv.src
+                    && before.get_field_ref(&other.src) == Some(played.piece)
+            })
+            .collect();
+        if !rivals.is_empty() {
+            if rivals
+                .iter()
+                .all(|other| other.src.get_x() != mv.src.get_x())
+            {
+                san.push(mv.src.file().to_char());
+            } else if rivals
+                .iter()
+                .all(|other| other.src.get_y() != mv.src.get_y())
+            {
+                san.push_str(&mv.src.rank().to_string());
+            } else {
+                san.push_str(&mv.src.to_string());
+            }
+        }
+    }
+
+    if played.castling.is_none() {
+        if played.captured.is_some() {
+            san.push('x');
+        }
+        san.push_str(&mv.dst.to_string());
+        if let Some(promotion) = mv.promotion {
+            san.push('=');
+            san.push_str(piece_letter(promotion));
+        }
+    }
+
+    if is_mate {
+        san.push('#');
+    } else if played.check {
+        san.push('+');
+    }
+
+    san
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::{Piece, Position};
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for mv in moves {
+            let (src, dst) = mv.split_at(2);
+            let mv = Move::new(
+                Position::try_from(src).unwrap(),
+                Position::try_from(dst).unwrap(),
+            );
+            game.play_move(mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn termination_uses_the_standard_values_and_comments_the_reason() {
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["e2e4"]);
+        game.resign(Turn::BlackPlays).unwrap();
+
+        let pgn = to_pgn(&game, &[]);
+        assert!(pgn.contains("[Termination \"normal\"]\n"));
+        assert!(pgn.ends_with("\n1. e4 {resignation} 1-0\n"));
+        assert!(!pgn.contains("SetUp"));
+    }
+
+    #[test]
+    fn set_up_positions_are_exported_with_their_fen() {
+        let fen = "4k3/8/8/8/8/8/8/4K2R w K - 0 12";
+        let mut game = ChessGame::from_fen(fen).unwrap();
+        play(&mut game, &["e1g1", "e8d8"]);
+
+        let pgn = to_pgn(&game, &[]);
+        assert!(pgn.contains("[SetUp \"1\"]\n[FEN \"4k3/8/8/8/8/8/8/4K2R w K - 0 12\"]\n"));
+        assert!(pgn.ends_with("\n12. O-O Kd8 *\n"));
+    }
+
+    #[test]
+    fn games_from_tiles_are_exported_with_their_fen() {
+        let mut tiles = vec![vec![None; 8]; 8];
+        tiles[0][0] = Some(Piece::White(PieceType::King));
+        tiles[7][7] = Some(Piece::Black(PieceType::King));
+        let game = ChessGame::from_tiles(tiles, Turn::BlackPlays).unwrap();
+
+        let pgn = to_pgn(&game, &[]);
+        assert!(pgn.contains("[FEN \"7k/8/8/8/8/8/8/K7 b - - 0 1\"]\n"));
+    }
+}
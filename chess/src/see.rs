@@ -0,0 +1,125 @@
+use crate::movegen::{self, Move};
+use crate::solution::{ChessGame, PieceType, Position, Tiles, Turn};
+use crate::stats::piece_value;
+
+// Static exchange evaluation: material won (positive) or lost by the player
+// making the capture once both sides keep recapturing on the target square
+// with their least valuable piece, each side may stop when it is ahead.
+// Checks, pins and the rules of variants are not taken into account.
+pub fn static_exchange(game: &ChessGame, mv: Move) -> i32 {
+    let color = match game.get_field_ref(&mv.src) {
+        Some(piece) => piece.color(),
+        None => return 0,
+    };
+
+    let mut tiles = game.tiles.clone();
+    let mut gains = vec![captured_value(&tiles, &mv)];
+    movegen::apply_to_tiles(&mut tiles, &mv);
+
+    let mut side = color.opponent();
+    while let Some(src) = least_valuable_attacker(&tiles, &mv.dst, side) {
+        let promotion = recapture_promotion(game, &tiles, &src, &mv.dst, side);
+        let recapture = Move {
+            src,
+            dst: mv.dst,
+            promotion,
+        };
+
+        // what the side to move gets, minus what the other side got before
+        let gain = captured_value(&tiles, &recapture) - gains[gains.len() - 1];
+        gains.push(gain);
+        // pieces behind the capturing one are found on the next search
+        movegen::apply_to_tiles(&mut tiles, &recapture);
+        side = side.opponent();
+    }
+
+    // every side captures only when it does not lose by it
+    while gains.len() > 1 {
+        let last = gains.pop().unwrap();
+        let previous = gains.last_mut().unwrap();
+        *previous = -(-*previous).max(last);
+    }
+
+    gains[0]
+}
+
+// value of the captured piece plus what a promotion adds
+fn captured_value(tiles: &Tiles, mv: &Move) -> i32 {
+    let captured = tiles[mv.dst.get_y()][mv.dst.get_x()]
+        .map_or(0, |piece| piece_value(piece.piece_type()) as i32);
+    let promoted = mv.promotion.map_or(0, |piece_type| {
+        piece_value(piece_type) as i32 - piece_value(PieceType::Pawn) as i32
+    });
+
+    captured + promoted
+}
+
+// the king captures last and only when the square is not defended anymore
+fn least_valuable_attacker(tiles: &Tiles, pos: &Position, side: Turn) -> Option<Position> {
+    let piece_type = |pos: &Position| tiles[pos.get_y()][pos.get_x()].unwrap().piece_type();
+    let attackers = movegen::attackers(tiles, pos, side);
+    let attacker = attackers
+        .iter()
+        .filter(|from| piece_type(from) != PieceType::King)
+        .min_by_key(|from| piece_value(piece_type(from)))
+        .or_else(|| attackers.first())
+        .copied()?;
+
+    if piece_type(&attacker) == PieceType::King
+        && !movegen::attackers(tiles, pos, side.opponent()).is_empty()
+    {
+        return None;
+    }
+
+    Some(attacker)
+}
+
+// a pawn recapturing on the last rank promotes to the first promotion piece
+fn recapture_promotion(
+    game: &ChessGame,
+    tiles: &Tiles,
+    src: &Position,
+    dst: &Position,
+    side: Turn,
+) -> Option<PieceType> {
+    let is_pawn =
+        tiles[src.get_y()][src.get_x()].is_some_and(|piece| piece.piece_type() == PieceType::Pawn);
+    if is_pawn && dst.get_y() == movegen::promotion_row(game.setup(), side) {
+        game.setup().promotions().first().copied()
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange(fen: &str, mv: &str) -> i32 {
+        let game = ChessGame::from_fen(fen).unwrap();
+        let (src, dst) = mv.split_at(2);
+        let mv = Move::new(
+            Position::try_from(src).unwrap(),
+            Position::try_from(dst).unwrap(),
+        );
+        static_exchange(&game, mv)
+    }
+
+    #[test]
+    fn pawn_takes_a_free_knight() {
+        assert_eq!(exchange("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 3);
+    }
+
+    #[test]
+    fn queen_takes_a_defended_pawn() {
+        // the rook behind the queen takes back, white still gives Q for R+P
+        assert_eq!(exchange("3r3k/8/8/3p4/8/8/3Q4/3R3K w - - 0 1", "d2d5"), -3);
+    }
+
+    #[test]
+    fn queen_behind_the_rook_wins_the_knight() {
+        assert_eq!(exchange("3r3k/8/8/3n4/8/8/3R4/3Q3K w - - 0 1", "d2d5"), 3);
+        // without the queen the rook is lost for the knight
+        assert_eq!(exchange("3r3k/8/8/3n4/8/8/3R4/7K w - - 0 1", "d2d5"), -2);
+    }
+}
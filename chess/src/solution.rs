@@ -1,97 +1,139 @@
 use core::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::board::BoardSetup;
+use crate::castling::{self, CastlingRights};
+use crate::events::{GameEvent, Observers};
+use crate::fen::StartCounters;
+use crate::movegen::{Move, PlayedMove};
+use crate::square::File;
+use crate::time_control::{ClockState, GameClock};
+use crate::variants::{Rules, Standard};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Error {
     PositionOutOfBounds,
     InvalidPositionFormat,
     InvalidMove,
+    GameOver,
+    InvalidSetup,
+    NoDrawOffer,
+    InvalidFen,
+    InvalidTimeControl,
 }
 
-pub const TILES_SIZE: usize = 8;
-pub const VALID_COLUMNS: [&str; 8] = ["a", "b", "c", "d", "e", "f", "g", "h"];
+// columns are named by a single letter
+pub const MAX_TILES_SIZE: usize = 26;
+
+// tiles[row][column]
+// tiles[0][0] is left-down
+pub type Tiles = Vec<Vec<Option<Piece>>>;
 
 #[derive(Debug)]
 pub struct ChessGame {
-    // tiles[row][column]
-    // tiles[0][0] is left-down
-    pub tiles: [[Option<Piece>; TILES_SIZE]; TILES_SIZE],
+    pub tiles: Tiles,
     next_turn: Turn,
+    clock: Option<GameClock>,
+    outcome: Option<GameOutcome>,
+    // player whose draw offer waits for an answer
+    draw_offer: Option<Turn>,
+    pub(crate) castling: CastlingRights,
+    // square skipped by the last move if it was a double pawn push
+    pub(crate) en_passant: Option<Position>,
+    history: Vec<PlayedMove>,
+    // state before each move of the history
+    undo: Vec<UndoState>,
+    pub(crate) rules: Rc<dyn Rules>,
+    pub(crate) setup: Rc<BoardSetup>,
+    // from the FEN the game was set up with
+    pub(crate) start: StartCounters,
+    pub(crate) observers: Observers,
+}
+
+// what a move changes besides the history
+#[derive(Debug, Clone)]
+pub(crate) struct UndoState {
+    tiles: Tiles,
+    castling: CastlingRights,
+    en_passant: Option<Position>,
+    clock: Option<ClockState>,
 }
 
 impl ChessGame {
-    pub fn new_game() -> Self {
+    // set up an arbitrary position, e.g. a puzzle
+    // the tiles have to be a rectangle of at most MAX_TILES_SIZE
+    pub fn from_tiles(tiles: Tiles, next_turn: Turn) -> Result<Self, Error> {
+        let width = tiles.first().map_or(0, |row| row.len());
+        if tiles.iter().any(|row| row.len() != width) {
+            return Err(Error::InvalidSetup);
+        }
+        let setup = BoardSetup::empty(width, tiles.len()).map_err(|_| Error::InvalidSetup)?;
+
+        Ok(ChessGame::with_setup(tiles, next_turn, Rc::new(setup)))
+    }
+    // the tiles have to fit the setup
+    fn with_setup(tiles: Tiles, next_turn: Turn, setup: Rc<BoardSetup>) -> Self {
         ChessGame {
-            next_turn: Turn::WhitePlays,
-            tiles: [
-                [
-                    // row 1
-                    Some(Piece::White(PieceType::Rook)),
-                    Some(Piece::White(PieceType::Knight)),
-                    Some(Piece::White(PieceType::Bishop)),
-                    Some(Piece::White(PieceType::Queen)),
-                    Some(Piece::White(PieceType::King)),
-                    Some(Piece::White(PieceType::Bishop)),
-                    Some(Piece::White(PieceType::Knight)),
-                    Some(Piece::White(PieceType::Rook)),
-                ],
-                [
-                    // row 2
-                    Some(Piece::White(PieceType::Pawn)),
-                    Some(Piece::White(PieceType::Pawn)),
-                    Some(Piece::White(PieceType::Pawn)),
-                    Some(Piece::White(PieceType::Pawn)),
-                    Some(Piece::White(PieceType::Pawn)),
-                    Some(Piece::White(PieceType::Pawn)),
-                    Some(Piece::White(PieceType::Pawn)),
-                    Some(Piece::White(PieceType::Pawn)),
-                ],
-                [
-                    // row 3
-                    None, None, None, None, None, None, None, None,
-                ],
-                [
-                    // row 4
-                    None, None, None, None, None, None, None, None,
-                ],
-                [
-                    // row 5
-                    None, None, None, None, None, None, None, None,
-                ],
-                [
-                    // row 6
-                    None, None, None, None, None, None, None, None,
-                ],
-                [
-                    // row 7
-                    Some(Piece::Black(PieceType::Pawn)),
-                    Some(Piece::Black(PieceType::Pawn)),
-                    Some(Piece::Black(PieceType::Pawn)),
-                    Some(Piece::Black(PieceType::Pawn)),
-                    Some(Piece::Black(PieceType::Pawn)),
-                    Some(Piece::Black(PieceType::Pawn)),
-                    Some(Piece::Black(PieceType::Pawn)),
-                    Some(Piece::Black(PieceType::Pawn)),
-                ],
-                [
-                    // row 8
-                    Some(Piece::Black(PieceType::Rook)),
-                    Some(Piece::Black(PieceType::Knight)),
-                    Some(Piece::Black(PieceType::Bishop)),
-                    Some(Piece::Black(PieceType::Queen)),
-                    Some(Piece::Black(PieceType::King)),
-                    Some(Piece::Black(PieceType::Bishop)),
-                    Some(Piece::Black(PieceType::Knight)),
-                    Some(Piece::Black(PieceType::Rook)),
-                ],
-            ],
+            tiles,
+            next_turn,
+            clock: None,
+            outcome: None,
+            draw_offer: None,
+            castling: CastlingRights::none(),
+            en_passant: None,
+            history: Vec::new(),
+            undo: Vec::new(),
+            rules: Rc::new(Standard),
+            setup,
+            start: StartCounters::default(),
+            observers: Observers::default(),
+        }
+    }
+    // play a chess variant, e.g. ChessGame::new_game().with_rules(Atomic)
+    pub fn with_rules<R: Rules + 'static>(mut self, rules: R) -> Self {
+        self.rules = Rc::new(rules);
+        self
+    }
+    // copy of the position and rules only, used when searching through moves
+    pub(crate) fn board_copy(&self) -> ChessGame {
+        self.board_copy_with_turn(self.next_turn)
+    }
+    pub(crate) fn board_copy_with_turn(&self, next_turn: Turn) -> ChessGame {
+        let mut copy = ChessGame::with_setup(self.tiles.clone(), next_turn, self.setup.clone());
+        copy.rules = self.rules.clone();
+        copy.castling = self.castling;
+        // only the player on turn may capture en passant
+        if next_turn == self.next_turn {
+            copy.en_passant = self.en_passant;
         }
+        copy
+    }
+    pub fn new_game() -> Self {
+        ChessGame::from_setup(BoardSetup::standard())
+    }
+    // e.g. BoardSetup::capablanca() for a 10x8 board
+    pub fn from_setup(setup: BoardSetup) -> Self {
+        let tiles = setup.starting_tiles();
+        let mut game = ChessGame::with_setup(tiles, Turn::WhitePlays, Rc::new(setup));
+        game.castling = CastlingRights::from_tiles(&game.tiles);
+        game
+    }
+    pub fn setup(&self) -> &BoardSetup {
+        self.setup.as_ref()
+    }
+    pub fn width(&self) -> usize {
+        self.setup.width()
+    }
+    pub fn height(&self) -> usize {
+        self.setup.height()
     }
     pub fn get_field(&self, pos: Position) -> Option<Piece> {
         //println!("Getting field from indices: [{}][{}]", pos.x, pos.y);
         self.get_field_ref(&pos)
     }
     pub fn get_field_ref(&self, pos: &Position) -> Option<Piece> {
-        if pos.get_x() >= TILES_SIZE || pos.get_y() >= TILES_SIZE {
+        if !self.contains(pos) {
             // println!("Unsatisfied condition in get_field_ref");
             None
         } else {
@@ -124,22 +166,301 @@ impl ChessGame {
 
     //     Ok(())
     // }
-    fn update_turn(&mut self) {
-        self.next_turn = match self.next_turn {
-            Turn::BlackPlays => Turn::WhitePlays,
-            Turn::WhitePlays => Turn::BlackPlays,
-        }
+    pub(crate) fn update_turn(&mut self) {
+        self.next_turn = self.next_turn.opponent();
     }
     pub fn current_player(&self) -> Turn {
         self.next_turn
     }
+    pub fn outcome(&self) -> Option<GameOutcome> {
+        self.outcome
+    }
+    pub fn history(&self) -> &[PlayedMove] {
+        &self.history
+    }
+    pub fn rules(&self) -> &dyn Rules {
+        self.rules.as_ref()
+    }
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+    // square a pawn of the player on turn may capture on en passant
+    pub fn en_passant_target(&self) -> Option<Position> {
+        self.en_passant
+    }
+    // e.g. for a position set up by from_tiles
+    pub fn set_castling_rights(&mut self, castling: CastlingRights) {
+        self.castling = castling;
+    }
+    // position in which the index-th move of the history was played
+    pub(crate) fn position_before(&self, index: usize) -> ChessGame {
+        let mut copy = self.board_copy_with_turn(self.history[index].piece.color());
+        copy.tiles = self.undo[index].tiles.clone();
+        copy.castling = self.undo[index].castling;
+        copy.en_passant = self.undo[index].en_passant;
+        copy
+    }
+    // position the game was set up with, with its move counters
+    pub(crate) fn starting_position(&self) -> ChessGame {
+        let mut copy = match self.history.first() {
+            Some(_) => self.position_before(0),
+            None => self.board_copy(),
+        };
+        copy.start = self.start;
+        copy
+    }
+    pub(crate) fn undo_state(&self) -> UndoState {
+        UndoState {
+            tiles: self.tiles.clone(),
+            castling: self.castling,
+            en_passant: self.en_passant,
+            clock: self.clock.as_ref().map(|clock| clock.state()),
+        }
+    }
+    pub fn checks_given(&self, color: Turn) -> usize {
+        self.start.checks(color)
+            + self
+                .history
+                .iter()
+                .filter(|played| played.check && played.piece.color() == color)
+                .count()
+    }
+
+    // the clock of the player on turn starts running
+    pub fn set_clock(&mut self, mut clock: GameClock) {
+        clock.start(self.next_turn);
+        self.clock = Some(clock);
+    }
+    pub fn clock(&self) -> Option<&GameClock> {
+        self.clock.as_ref()
+    }
+
+    // ends the game when the player on turn ran out of time
+    pub fn check_flag(&mut self) -> Option<GameOutcome> {
+        if self.outcome.is_none() {
+            if let Some(flagged) = self.clock.as_ref().and_then(|clock| clock.flagged()) {
+                let opponent = flagged.opponent();
+                let result = if self.has_mating_material(opponent) {
+                    GameResult::win_for(opponent)
+                } else {
+                    GameResult::Draw
+                };
+                self.end_game(GameOutcome {
+                    result,
+                    termination: Termination::TimeForfeit,
+                });
+            }
+        }
+
+        self.outcome
+    }
+
+    // a lone king cannot mate, neither can a king and a knight or a king and
+    // bishops on squares of one color against a lone king, against more
+    // pieces they can mate when the opponent blocks their own king
+    pub fn has_mating_material(&self, color: Turn) -> bool {
+        let mut knights = 0;
+        let mut bishop_square_colors = Vec::new();
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, field) in row.iter().enumerate() {
+                match field {
+                    Some(piece) if piece.color() == color => match piece.piece_type() {
+                        PieceType::King => (),
+                        PieceType::Knight => knights += 1,
+                        PieceType::Bishop => bishop_square_colors.push((x + y) % 2),
+                        PieceType::Pawn
+                        | PieceType::Rook
+                        | PieceType::Queen
+                        | PieceType::Archbishop
+                        | PieceType::Chancellor => return true,
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        let opponent_has_pieces = self
+            .tiles
+            .iter()
+            .flatten()
+            .flatten()
+            .any(|piece| piece.color() != color && piece.piece_type() != PieceType::King);
+        match (knights, bishop_square_colors.as_slice()) {
+            (0, []) => false,
+            (1, []) => opponent_has_pieces,
+            (0, [first, rest @ ..]) => {
+                opponent_has_pieces || rest.iter().any(|square_color| square_color != first)
+            }
+            _ => true,
+        }
+    }
+
+    // called before a move is made, fails when the game is already over
+    pub(crate) fn start_move(&mut self) -> Result<(), Error> {
+        match self.check_flag() {
+            Some(_) => Err(Error::GameOver),
+            None => Ok(()),
+        }
+    }
+    // called after a move is made on the tiles
+    pub(crate) fn finish_move(
+        &mut self,
+        mv: Move,
+        piece: Piece,
+        captured: Option<Piece>,
+        before: UndoState,
+    ) {
+        if let Some(clock) = &mut self.clock {
+            clock.press();
+        }
+        self.update_turn();
+
+        // moving instead of answering declines the opponent's offer
+        if self.draw_offer == Some(self.next_turn) {
+            self.draw_offer = None;
+        }
+
+        let is_check = self.is_check();
+        let rook_move = castling::rook_move(&before.tiles, &mv);
+        let played = PlayedMove {
+            mv,
+            piece,
+            captured,
+            castling: rook_move.and(castling::castling_side(&before.tiles, &mv)),
+            check: is_check,
+        };
+        self.history.push(played);
+        self.undo.push(before);
+
+        let mut events = vec![GameEvent::MoveMade {
+            mv: played.mv,
+            piece: played.piece,
+        }];
+        if let Some(captured) = played.captured {
+            events.push(GameEvent::Capture {
+                mv: played.mv,
+                captured,
+            });
+        }
+        if let Some(piece_type) = played.mv.promotion {
+            events.push(GameEvent::Promotion {
+                mv: played.mv,
+                piece: Piece::new(played.piece.color(), piece_type),
+            });
+        }
+        if let Some((rook_src, rook_dst)) = rook_move {
+            events.push(GameEvent::Castling {
+                mv: played.mv,
+                rook: Move::new(rook_src, rook_dst),
+            });
+        }
+        if is_check {
+            events.push(GameEvent::Check {
+                color: self.next_turn,
+            });
+        }
+        self.notify_observers(&events);
+
+        if let Some(outcome) = self.rules.outcome(self) {
+            self.end_game(outcome);
+        }
+    }
+
+    fn end_game(&mut self, outcome: GameOutcome) {
+        self.outcome = Some(outcome);
+        self.draw_offer = None;
+        self.notify_observers(&[GameEvent::GameEnd { outcome }]);
+    }
+
+    pub fn resign(&mut self, color: Turn) -> Result<GameOutcome, Error> {
+        self.start_move()?;
+
+        let outcome = GameOutcome {
+            result: GameResult::win_for(color.opponent()),
+            termination: Termination::Resignation,
+        };
+        self.end_game(outcome);
+
+        Ok(outcome)
+    }
+
+    pub fn draw_offer(&self) -> Option<Turn> {
+        self.draw_offer
+    }
+    pub fn offer_draw(&mut self, color: Turn) -> Result<(), Error> {
+        self.start_move()?;
+
+        self.draw_offer = Some(color);
+        self.notify_observers(&[GameEvent::DrawOffered { color }]);
+
+        Ok(())
+    }
+    // color is the player answering the offer
+    pub fn accept_draw(&mut self, color: Turn) -> Result<GameOutcome, Error> {
+        self.start_move()?;
+        if self.draw_offer != Some(color.opponent()) {
+            return Err(Error::NoDrawOffer);
+        }
+
+        let outcome = GameOutcome {
+            result: GameResult::Draw,
+            termination: Termination::Agreement,
+        };
+        self.end_game(outcome);
+
+        Ok(outcome)
+    }
+    pub fn decline_draw(&mut self, color: Turn) -> Result<(), Error> {
+        if self.draw_offer != Some(color.opponent()) {
+            return Err(Error::NoDrawOffer);
+        }
+
+        self.draw_offer = None;
+        self.notify_observers(&[GameEvent::DrawDeclined { color }]);
+
+        Ok(())
+    }
+
+    // takes back the last move, the clocks are set back to how they were
+    // before it and the player who made it is thinking again
+    // games ended by the players or the clock cannot be taken back
+    pub fn undo_move(&mut self) -> Result<Move, Error> {
+        if let Some(GameOutcome {
+            termination:
+                Termination::TimeForfeit | Termination::Resignation | Termination::Agreement,
+            ..
+        }) = self.outcome
+        {
+            return Err(Error::GameOver);
+        }
+        let (played, before) = match (self.history.pop(), self.undo.pop()) {
+            (Some(played), Some(before)) => (played, before),
+            _ => return Err(Error::InvalidMove),
+        };
+
+        self.tiles = before.tiles;
+        self.castling = before.castling;
+        self.en_passant = before.en_passant;
+        self.outcome = None;
+        self.update_turn();
+        if let Some(clock) = &mut self.clock {
+            if let Some(state) = before.clock {
+                clock.restore(state);
+            }
+            clock.start(self.next_turn);
+        }
+
+        self.notify_observers(&[GameEvent::Undo { mv: played.mv }]);
+
+        Ok(played.mv)
+    }
 
     pub fn make_move(&mut self, src: Position, dst: Position) -> Result<Option<Piece>, Error> {
         self.make_move_ref(&src, &dst)
     }
 
     fn make_move_ref(&mut self, src: &Position, dst: &Position) -> Result<Option<Piece>, Error> {
-        let mut returned_piece: Option<Piece> = None;
+        self.start_move()?;
 
         if src == dst {
             return Err(Error::InvalidMove);
@@ -147,7 +468,11 @@ impl ChessGame {
 
         //let (dist_x, dist_y) = src.distance_from(dst);
 
-        let moving_piece = match self.tiles[src.get_y()][src.get_x()] {
+        if !self.contains(dst) {
+            return Err(Error::PositionOutOfBounds);
+        }
+
+        let moving_piece = match self.get_field_ref(src) {
             Some(piece) => piece,
             None => return Err(Error::InvalidMove),
         };
@@ -158,10 +483,8 @@ impl ChessGame {
             }
 
             // cannot move to position of piece with the same color
-            if let Some(piece) = self.get_field_ref(dst) {
-                if let Piece::White(_) = piece {
-                    return Err(Error::InvalidMove);
-                }
+            if let Some(Piece::White(_)) = self.get_field_ref(dst) {
+                return Err(Error::InvalidMove);
             }
         } else {
             // Turn::Black
@@ -170,24 +493,18 @@ impl ChessGame {
             }
 
             // cannot move to position of piece with the same color
-            if let Some(piece) = self.get_field_ref(dst) {
-                if let Piece::Black(_) = piece {
-                    return Err(Error::InvalidMove);
-                }
+            if let Some(Piece::Black(_)) = self.get_field_ref(dst) {
+                return Err(Error::InvalidMove);
             }
         }
 
         // everything is valid
         // make the move
-        let dst_piece_opt = self.get_field_ref(dst);
-        if let Some(dst_piece) = dst_piece_opt {
-            returned_piece = Some(dst_piece);
-        }
-
-        self.tiles[dst.get_y()][dst.get_x()] = Some(moving_piece);
-        self.tiles[src.get_y()][src.get_x()] = None;
+        let mv = Move::new(*src, *dst);
+        let before = self.undo_state();
+        let returned_piece = self.move_pieces(&mv, moving_piece);
 
-        self.update_turn();
+        self.finish_move(mv, moving_piece, returned_piece, before);
 
         Ok(returned_piece)
     }
@@ -205,14 +522,20 @@ impl TryFrom<&str> for Position {
     fn try_from(s: &str) -> Result<Position, Error> {
         // println!("Trying from {}", s);
 
-        if s.len() != 2 {
+        // column letter followed by the row number, e.g. "e4" or "a10"
+        let mut chars = s.chars();
+        let column = match chars.next().map(File::from_char) {
+            Some(Ok(file)) => file,
+            _ => return Err(Error::InvalidPositionFormat),
+        };
+        let row_str = chars.as_str();
+        if !row_str.chars().all(|c| c.is_ascii_digit()) {
             return Err(Error::InvalidPositionFormat);
         }
 
-        let column = s.chars().nth(0).unwrap().to_string().to_lowercase();
         let row: usize;
-        if let Ok(num) = s.chars().nth(1).unwrap().to_string().parse::<usize>() {
-            if num > TILES_SIZE || num <= 0 {
+        if let Ok(num) = row_str.parse::<usize>() {
+            if num > MAX_TILES_SIZE || num == 0 {
                 return Err(Error::InvalidPositionFormat);
             } else {
                 //println!("Loading {}", s);
@@ -222,20 +545,17 @@ impl TryFrom<&str> for Position {
             return Err(Error::InvalidPositionFormat);
         }
 
-        if !VALID_COLUMNS.contains(&column.as_str()) || row >= TILES_SIZE {
-            return Err(Error::InvalidPositionFormat);
-        }
-        
-        let col_index = VALID_COLUMNS.iter().position(|r| r == &column).unwrap();
+        let col_index = column.index();
         //println!("Loaded on indices: [{}][{}]", row, col_index);
 
-        Ok( Position::new(col_index, row).unwrap() )
+        Ok(Position::new(col_index, row).unwrap())
     }
 }
 
 impl Position {
+    // the position may still be outside of a smaller board
     pub fn new(x: usize, y: usize) -> Result<Self, Error> {
-        if x >= TILES_SIZE || y >= TILES_SIZE {
+        if x >= MAX_TILES_SIZE || y >= MAX_TILES_SIZE {
             Err(Error::PositionOutOfBounds)
         } else {
             Ok(Position { x, y })
@@ -277,7 +597,94 @@ pub enum Turn {
     BlackPlays,
 }
 
+impl Turn {
+    pub fn opponent(&self) -> Turn {
+        match self {
+            Turn::WhitePlays => Turn::BlackPlays,
+            Turn::BlackPlays => Turn::WhitePlays,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GameResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameResult {
+    pub fn win_for(color: Turn) -> GameResult {
+        match color {
+            Turn::WhitePlays => GameResult::WhiteWins,
+            Turn::BlackPlays => GameResult::BlackWins,
+        }
+    }
+    // PGN allows only ASCII
+    pub fn to_pgn(&self) -> &'static str {
+        match self {
+            GameResult::WhiteWins => "1-0",
+            GameResult::BlackWins => "0-1",
+            GameResult::Draw => "1/2-1/2",
+        }
+    }
+}
+
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameResult::Draw => write!(f, "½-½"),
+            _ => write!(f, "{}", self.to_pgn()),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    TimeForfeit,
+    Resignation,
+    Agreement,
+    // variants
+    KingOfTheHill,
+    ThreeChecks,
+    KingExploded,
+}
+
+impl Termination {
+    // value of the PGN Termination tag, the reason itself goes to a comment
+    pub fn to_pgn(&self) -> &'static str {
+        match self {
+            Termination::TimeForfeit => "time forfeit",
+            _ => "normal",
+        }
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::TimeForfeit => "time forfeit",
+            Termination::Resignation => "resignation",
+            Termination::Agreement => "draw by agreement",
+            Termination::KingOfTheHill => "king reached the hill",
+            Termination::ThreeChecks => "three checks",
+            Termination::KingExploded => "king exploded",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GameOutcome {
+    pub result: GameResult,
+    pub termination: Termination,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum PieceType {
     Rook,   // veza
     Knight, // kon
@@ -285,6 +692,9 @@ pub enum PieceType {
     Queen,  // kralovna
     King,   // kral
     Pawn,   // pesiak
+    // fairy pieces
+    Archbishop, // strelec + kon
+    Chancellor, // veza + kon
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -292,3 +702,168 @@ pub enum Piece {
     White(PieceType),
     Black(PieceType),
 }
+
+impl Piece {
+    pub fn new(color: Turn, piece_type: PieceType) -> Piece {
+        match color {
+            Turn::WhitePlays => Piece::White(piece_type),
+            Turn::BlackPlays => Piece::Black(piece_type),
+        }
+    }
+    pub fn color(&self) -> Turn {
+        match self {
+            Piece::White(_) => Turn::WhitePlays,
+            Piece::Black(_) => Turn::BlackPlays,
+        }
+    }
+    pub fn piece_type(&self) -> PieceType {
+        match self {
+            Piece::White(piece_type) | Piece::Black(piece_type) => *piece_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::movegen::Move;
+    use crate::time_control::{ManualTimeSource, TimeControl};
+
+    #[test]
+    fn from_tiles_rejects_boards_that_are_not_rectangles_of_the_allowed_size() {
+        let ragged = vec![vec![None; 8], vec![None; 7]];
+        for tiles in [
+            ragged,
+            Vec::new(),
+            vec![Vec::new(); 8],
+            vec![vec![None; MAX_TILES_SIZE + 1]; 8],
+            vec![vec![None; 8]; MAX_TILES_SIZE + 1],
+        ] {
+            assert_eq!(
+                ChessGame::from_tiles(tiles, Turn::WhitePlays).err(),
+                Some(Error::InvalidSetup)
+            );
+        }
+
+        let game = ChessGame::from_tiles(vec![vec![None; 10]; 10], Turn::BlackPlays).unwrap();
+        assert_eq!((game.width(), game.height()), (10, 10));
+    }
+
+    fn flag(fen: &str) -> Option<GameOutcome> {
+        let time = Rc::new(ManualTimeSource::new());
+        let mut game = ChessGame::from_fen(fen).unwrap();
+        game.set_clock(GameClock::new(
+            TimeControl::sudden_death(Duration::from_secs(10)),
+            time.clone(),
+        ));
+        assert_eq!(game.check_flag(), None);
+
+        time.advance(Duration::from_secs(11));
+        game.check_flag()
+    }
+
+    #[test]
+    fn running_out_of_time_loses_against_mating_material() {
+        let outcome = flag("4k3/8/8/8/8/8/8/R3K3 b - - 0 1").unwrap();
+        assert_eq!(outcome.result, GameResult::WhiteWins);
+        assert_eq!(outcome.termination, Termination::TimeForfeit);
+
+        // the pawn could block its own king
+        let outcome = flag("4k3/4p3/8/8/8/8/8/4KN2 b - - 0 1").unwrap();
+        assert_eq!(outcome.result, GameResult::WhiteWins);
+    }
+
+    #[test]
+    fn running_out_of_time_draws_against_insufficient_material() {
+        let outcome = flag("4k3/8/8/8/8/8/8/4KN2 b - - 0 1").unwrap();
+        assert_eq!(outcome.result, GameResult::Draw);
+        assert_eq!(outcome.termination, Termination::TimeForfeit);
+    }
+
+    #[test]
+    fn mating_material() {
+        let has = |fen: &str| {
+            ChessGame::from_fen(fen)
+                .unwrap()
+                .has_mating_material(Turn::WhitePlays)
+        };
+        assert!(!has("4k3/8/8/8/8/8/8/4K3 w - - 0 1"));
+        assert!(!has("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1"));
+        assert!(has("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1"));
+        // bishops on b1 and c2 share a color, b1 and c1 do not
+        assert!(!has("4k3/8/8/8/8/8/2B5/1B2K3 w - - 0 1"));
+        assert!(has("4k3/8/8/8/8/8/8/1BB1K3 w - - 0 1"));
+        assert!(!has("4k3/8/8/8/8/8/8/1B2K3 w - - 0 1"));
+        assert!(has("4k3/8/8/8/8/8/8/1B2K2b w - - 0 1"));
+        assert!(has("4k3/8/8/8/8/8/8/4K2P w - - 0 1"));
+    }
+
+    fn play(game: &mut ChessGame, moves: &[&str]) {
+        for mv in moves {
+            let (src, dst) = mv.split_at(2);
+            let mv = Move::new(
+                Position::try_from(src).unwrap(),
+                Position::try_from(dst).unwrap(),
+            );
+            game.play_move(mv).unwrap();
+        }
+    }
+
+    #[test]
+    fn moving_instead_of_answering_declines_the_draw() {
+        let mut game = ChessGame::new_game();
+        game.offer_draw(Turn::WhitePlays).unwrap();
+        play(&mut game, &["e2e4"]);
+        assert_eq!(game.draw_offer(), Some(Turn::WhitePlays));
+
+        play(&mut game, &["e7e5"]);
+        assert_eq!(game.draw_offer(), None);
+        assert_eq!(game.accept_draw(Turn::BlackPlays), Err(Error::NoDrawOffer));
+    }
+
+    #[test]
+    fn only_the_opponent_answers_an_offer() {
+        let mut game = ChessGame::new_game();
+        assert_eq!(game.decline_draw(Turn::BlackPlays), Err(Error::NoDrawOffer));
+
+        game.offer_draw(Turn::WhitePlays).unwrap();
+        assert_eq!(game.accept_draw(Turn::WhitePlays), Err(Error::NoDrawOffer));
+        assert_eq!(game.decline_draw(Turn::WhitePlays), Err(Error::NoDrawOffer));
+        let outcome = game.accept_draw(Turn::BlackPlays).unwrap();
+        assert_eq!(outcome.result, GameResult::Draw);
+        assert_eq!(outcome.termination, Termination::Agreement);
+    }
+
+    #[test]
+    fn resigning_ends_the_game() {
+        let mut game = ChessGame::new_game();
+        game.offer_draw(Turn::BlackPlays).unwrap();
+        let outcome = game.resign(Turn::WhitePlays).unwrap();
+        assert_eq!(outcome.result, GameResult::BlackWins);
+        assert_eq!(outcome.termination, Termination::Resignation);
+        assert_eq!(game.outcome(), Some(outcome));
+        assert_eq!(game.draw_offer(), None);
+    }
+
+    #[test]
+    fn nothing_is_played_after_the_game_ended() {
+        let mut game = ChessGame::new_game();
+        game.resign(Turn::BlackPlays).unwrap();
+        let e4 = Move::new(
+            Position::try_from("e2").unwrap(),
+            Position::try_from("e4").unwrap(),
+        );
+        assert_eq!(game.play_move(e4), Err(Error::GameOver));
+        assert_eq!(game.resign(Turn::WhitePlays), Err(Error::GameOver));
+        assert_eq!(game.offer_draw(Turn::WhitePlays), Err(Error::GameOver));
+        assert_eq!(game.undo_move(), Err(Error::GameOver));
+
+        // a mated player cannot answer either
+        let mut game = ChessGame::new_game();
+        play(&mut game, &["f2f3", "e7e5", "g2g4", "d8h4"]);
+        assert_eq!(game.resign(Turn::WhitePlays), Err(Error::GameOver));
+        assert_eq!(game.play_move(e4), Err(Error::GameOver));
+    }
+}
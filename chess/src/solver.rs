@@ -0,0 +1,125 @@
+use crate::movegen::Move;
+use crate::solution::ChessGame;
+
+// Mate-in-N search used to verify puzzles.
+// N counts the moves of the side on turn, mates in fewer moves count too.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleVerdict {
+    NoMate,
+    Unique(Move),
+    // more than one first move forces mate
+    Cooked(Vec<Move>),
+}
+
+// mates in fewer moves are solutions too: a position whose only solution is
+// a mate in 1 is Unique for n = 2, though a composed mate in 2 must not mate
+// sooner, check with has_mate_in(game, n - 1) to tell them apart
+pub fn verify_puzzle(game: &ChessGame, n: usize) -> PuzzleVerdict {
+    let mut moves = mating_moves(game, n);
+    match moves.len() {
+        0 => PuzzleVerdict::NoMate,
+        1 => PuzzleVerdict::Unique(moves.remove(0)),
+        _ => PuzzleVerdict::Cooked(moves),
+    }
+}
+
+// every first move that forces checkmate in at most n moves
+pub fn mating_moves(game: &ChessGame, n: usize) -> Vec<Move> {
+    if n == 0 {
+        return Vec::new();
+    }
+
+    game.legal_moves()
+        .into_iter()
+        .filter(|mv| defender_is_mated(&after_move(game, mv), n))
+        .collect()
+}
+
+pub fn has_mate_in(game: &ChessGame, n: usize) -> bool {
+    n > 0
+        && game
+            .legal_moves()
+            .iter()
+            .any(|mv| defender_is_mated(&after_move(game, mv), n))
+}
+
+// n is the number of attacker's moves including the one just played
+fn defender_is_mated(game: &ChessGame, n: usize) -> bool {
+    let replies = game.legal_moves();
+    if replies.is_empty() {
+        // stalemate is not a win
+        return game.is_check();
+    }
+    if n == 1 {
+        return false;
+    }
+
+    replies
+        .iter()
+        .all(|mv| has_mate_in(&after_move(game, mv), n - 1))
+}
+
+fn after_move(game: &ChessGame, mv: &Move) -> ChessGame {
+    let mut next = game.board_copy();
+    next.apply_move(mv);
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution::Position;
+
+    fn mv(src: &str, dst: &str) -> Move {
+        Move::new(
+            Position::try_from(src).unwrap(),
+            Position::try_from(dst).unwrap(),
+        )
+    }
+
+    #[test]
+    fn unique_mate_in_two() {
+        // 1. Kf7 Kh7 2. Rh1#
+        let game = ChessGame::from_fen("7k/8/5K2/8/8/8/8/6R1 w - - 0 1").unwrap();
+        assert_eq!(
+            verify_puzzle(&game, 2),
+            PuzzleVerdict::Unique(mv("f6", "f7"))
+        );
+        assert!(!has_mate_in(&game, 1));
+    }
+
+    #[test]
+    fn cooked_mate_in_two() {
+        // 1. Kb6 Kb8 2. Rh8# and 1. Kc7 Ka7 2. Ra1#
+        let game = ChessGame::from_fen("k7/8/2K5/8/8/8/8/7R w - - 0 1").unwrap();
+        match verify_puzzle(&game, 2) {
+            PuzzleVerdict::Cooked(moves) => {
+                assert_eq!(moves.len(), 2);
+                assert!(moves.contains(&mv("c6", "b6")));
+                assert!(moves.contains(&mv("c6", "c7")));
+            }
+            verdict => panic!("expected a cook, got {:?}", verdict),
+        }
+    }
+
+    #[test]
+    fn no_mate_in_two() {
+        let game = ChessGame::from_fen("7k/8/8/8/8/8/8/K6R w - - 0 1").unwrap();
+        assert_eq!(verify_puzzle(&game, 2), PuzzleVerdict::NoMate);
+        assert!(mating_moves(&game, 2).is_empty());
+        assert_eq!(verify_puzzle(&game, 0), PuzzleVerdict::NoMate);
+    }
+
+    #[test]
+    fn en_passant_escapes_the_mate() {
+        // 1. b4+ is answered by cxb3 e.p.
+        let mut game = ChessGame::from_fen("8/8/3Q4/k7/2p5/2N5/1P6/7K w - - 0 1").unwrap();
+        assert!(!mating_moves(&game, 1).contains(&mv("b2", "b4")));
+
+        game.play_move(mv("b2", "b4")).unwrap();
+        assert!(game.is_check());
+        assert_eq!(game.outcome(), None);
+        assert_eq!(game.legal_moves(), [mv("c4", "b3")]);
+    }
+}
@@ -0,0 +1,282 @@
+use std::fmt;
+
+use crate::solution::{ChessGame, Error, Position, MAX_TILES_SIZE};
+
+// Typed files, ranks and directions on top of Position.
+
+// column, 'a' is 0
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct File(usize);
+
+impl File {
+    pub fn new(index: usize) -> Result<Self, Error> {
+        if index >= MAX_TILES_SIZE {
+            Err(Error::PositionOutOfBounds)
+        } else {
+            Ok(File(index))
+        }
+    }
+    pub fn from_char(c: char) -> Result<Self, Error> {
+        if !c.is_ascii_alphabetic() {
+            return Err(Error::InvalidPositionFormat);
+        }
+
+        File::new((c.to_ascii_lowercase() as u8 - b'a') as usize)
+    }
+    pub fn index(&self) -> usize {
+        self.0
+    }
+    pub fn to_char(&self) -> char {
+        (b'a' + self.0 as u8) as char
+    }
+    // files of a board of the given width, from 'a'
+    pub fn all(width: usize) -> impl Iterator<Item = File> {
+        (0..width.min(MAX_TILES_SIZE)).map(File)
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// row, rank 1 is 0
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Rank(usize);
+
+impl Rank {
+    pub fn new(index: usize) -> Result<Self, Error> {
+        if index >= MAX_TILES_SIZE {
+            Err(Error::PositionOutOfBounds)
+        } else {
+            Ok(Rank(index))
+        }
+    }
+    // the number printed on the board, starting at 1
+    pub fn from_number(number: usize) -> Result<Self, Error> {
+        if number == 0 {
+            return Err(Error::PositionOutOfBounds);
+        }
+
+        Rank::new(number - 1)
+    }
+    pub fn index(&self) -> usize {
+        self.0
+    }
+    pub fn number(&self) -> usize {
+        self.0 + 1
+    }
+    // ranks of a board of the given height, from 1
+    pub fn all(height: usize) -> impl Iterator<Item = Rank> {
+        (0..height.min(MAX_TILES_SIZE)).map(Rank)
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+// north is towards black's side of the board
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::NorthEast,
+        Direction::SouthEast,
+        Direction::SouthWest,
+        Direction::NorthWest,
+    ];
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    // (x, y) change of one step
+    pub fn delta(&self) -> (i8, i8) {
+        match self {
+            Direction::North => (0, 1),
+            Direction::NorthEast => (1, 1),
+            Direction::East => (1, 0),
+            Direction::SouthEast => (1, -1),
+            Direction::South => (0, -1),
+            Direction::SouthWest => (-1, -1),
+            Direction::West => (-1, 0),
+            Direction::NorthWest => (-1, 1),
+        }
+    }
+}
+
+impl Position {
+    pub fn from_file_rank(file: File, rank: Rank) -> Position {
+        Position {
+            x: file.index(),
+            y: rank.index(),
+        }
+    }
+    pub fn file(&self) -> File {
+        File(self.x)
+    }
+    pub fn rank(&self) -> Rank {
+        Rank(self.y)
+    }
+
+    // None when the result would leave the biggest possible board,
+    // use ChessGame::offset to stay on the game's board
+    pub fn offset(&self, dx: i8, dy: i8) -> Option<Position> {
+        let x = self.x.checked_add_signed(dx as isize)?;
+        let y = self.y.checked_add_signed(dy as isize)?;
+
+        Position::new(x, y).ok()
+    }
+    pub fn step(&self, direction: Direction) -> Option<Position> {
+        let (dx, dy) = direction.delta();
+        self.offset(dx, dy)
+    }
+
+    // a1, b1, ..., a2, b2, ... of a board of the given size
+    pub fn all(width: usize, height: usize) -> impl Iterator<Item = Position> {
+        Rank::all(height).flat_map(move |rank| {
+            File::all(width).map(move |file| Position::from_file_rank(file, rank))
+        })
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.file(), self.rank())
+    }
+}
+
+impl ChessGame {
+    // all squares of the board, from a1 along the ranks
+    pub fn squares(&self) -> impl Iterator<Item = Position> {
+        Position::all(self.width(), self.height())
+    }
+    pub fn contains(&self, pos: &Position) -> bool {
+        pos.get_x() < self.width() && pos.get_y() < self.height()
+    }
+    // None at the edge of the board
+    pub fn offset(&self, pos: &Position, dx: i8, dy: i8) -> Option<Position> {
+        pos.offset(dx, dy).filter(|next| self.contains(next))
+    }
+    pub fn step(&self, pos: &Position, direction: Direction) -> Option<Position> {
+        let (dx, dy) = direction.delta();
+        self.offset(pos, dx, dy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_and_ranks_convert_both_ways() {
+        let e = File::from_char('e').unwrap();
+        assert_eq!((e.index(), e.to_char()), (4, 'e'));
+        assert_eq!(File::from_char('E'), Ok(e));
+        assert_eq!(File::from_char('z').unwrap().index(), MAX_TILES_SIZE - 1);
+        assert_eq!(File::from_char('1'), Err(Error::InvalidPositionFormat));
+        assert_eq!(File::new(MAX_TILES_SIZE), Err(Error::PositionOutOfBounds));
+
+        let eighth = Rank::from_number(8).unwrap();
+        assert_eq!((eighth.index(), eighth.number()), (7, 8));
+        assert_eq!(Rank::new(7), Ok(eighth));
+        assert_eq!(Rank::from_number(0), Err(Error::PositionOutOfBounds));
+        assert_eq!(
+            Rank::from_number(MAX_TILES_SIZE + 1),
+            Err(Error::PositionOutOfBounds)
+        );
+
+        assert_eq!(File::all(30).count(), MAX_TILES_SIZE);
+        assert_eq!(
+            Rank::all(3).map(|rank| rank.number()).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn steps_stop_at_the_edges() {
+        let a1 = Position { x: 0, y: 0 };
+        for direction in [Direction::South, Direction::West, Direction::SouthEast] {
+            assert_eq!(a1.step(direction), None);
+        }
+        assert_eq!(a1.step(Direction::NorthEast), Some(Position { x: 1, y: 1 }));
+
+        let corner = Position {
+            x: MAX_TILES_SIZE - 1,
+            y: MAX_TILES_SIZE - 1,
+        };
+        assert_eq!(corner.step(Direction::North), None);
+        assert_eq!(corner.offset(1, -1), None);
+
+        // h8 is the corner of the standard board, but not of the biggest one
+        let game = ChessGame::new_game();
+        let h8 = Position { x: 7, y: 7 };
+        assert_eq!(game.step(&h8, Direction::NorthEast), None);
+        assert_eq!(game.step(&h8, Direction::East), None);
+        assert_eq!(h8.step(Direction::East), Some(Position { x: 8, y: 7 }));
+        assert_eq!(
+            game.step(&h8, Direction::SouthWest),
+            Some(Position { x: 6, y: 6 })
+        );
+    }
+
+    #[test]
+    fn opposite_directions_cancel() {
+        for direction in Direction::ALL {
+            let (dx, dy) = direction.delta();
+            let opposite = Direction::ALL
+                .iter()
+                .filter(|other| other.delta() == (-dx, -dy))
+                .count();
+            assert_eq!(opposite, 1, "{:?}", direction);
+        }
+        assert!(Direction::ORTHOGONAL
+            .iter()
+            .all(|direction| direction.delta().0 == 0 || direction.delta().1 == 0));
+        assert!(Direction::DIAGONAL
+            .iter()
+            .all(|direction| direction.delta().0 != 0 && direction.delta().1 != 0));
+    }
+
+    #[test]
+    fn all_positions_go_along_the_ranks() {
+        let names: Vec<String> = Position::all(2, 2).map(|pos| pos.to_string()).collect();
+        assert_eq!(names, ["a1", "b1", "a2", "b2"]);
+        assert_eq!(ChessGame::new_game().squares().count(), 64);
+        assert_eq!(Position::all(30, 1).count(), MAX_TILES_SIZE);
+    }
+
+    #[test]
+    fn display() {
+        let pos = Position::from_file_rank(File::new(9).unwrap(), Rank::from_number(10).unwrap());
+        assert_eq!(pos.to_string(), "j10");
+        assert_eq!(pos.file().to_string(), "j");
+        assert_eq!(pos.rank().to_string(), "10");
+    }
+}
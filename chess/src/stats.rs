@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::solution::{ChessGame, Piece, PieceType, Position, Turn};
+
+// Material, piece lists, mobility and pawn structure of a position.
+
+// standard point values, the king is not counted
+pub fn piece_value(piece_type: PieceType) -> u32 {
+    match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight | PieceType::Bishop => 3,
+        PieceType::Rook => 5,
+        PieceType::Archbishop => 7,
+        PieceType::Chancellor => 8,
+        PieceType::Queen => 9,
+        PieceType::King => 0,
+    }
+}
+
+impl ChessGame {
+    // pieces of one color together with their positions, in board order
+    pub fn pieces_of(&self, color: Turn) -> Vec<(Position, Piece)> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(x, field)| field.map(|piece| (Position { x, y }, piece)))
+            })
+            .filter(|(_, piece)| piece.color() == color)
+            .collect()
+    }
+
+    pub fn piece_list(&self, piece: Piece) -> Vec<Position> {
+        self.pieces_of(piece.color())
+            .into_iter()
+            .filter(|(_, other)| *other == piece)
+            .map(|(pos, _)| pos)
+            .collect()
+    }
+
+    pub fn piece_counts(&self, color: Turn) -> HashMap<PieceType, usize> {
+        let mut counts = HashMap::new();
+        for (_, piece) in self.pieces_of(color) {
+            *counts.entry(piece.piece_type()).or_insert(0) += 1;
+        }
+
+        counts
+    }
+
+    pub fn material(&self, color: Turn) -> u32 {
+        self.pieces_of(color)
+            .iter()
+            .map(|(_, piece)| piece_value(piece.piece_type()))
+            .sum()
+    }
+
+    // positive when white is ahead
+    pub fn material_balance(&self) -> i32 {
+        self.material(Turn::WhitePlays) as i32 - self.material(Turn::BlackPlays) as i32
+    }
+
+    // number of legal moves the player would have if it was on turn
+    pub fn mobility(&self, color: Turn) -> usize {
+        self.board_copy_with_turn(color).legal_moves().len()
+    }
+
+    // pawns sharing their file with another pawn of the same color
+    pub fn doubled_pawns(&self, color: Turn) -> Vec<Position> {
+        let pawns = self.piece_list(Piece::new(color, PieceType::Pawn));
+        pawns
+            .iter()
+            .filter(|pawn| {
+                pawns
+                    .iter()
+                    .any(|other| other.get_x() == pawn.get_x() && other != *pawn)
+            })
+            .copied()
+            .collect()
+    }
+
+    // pawns without a pawn of the same color on the neighbouring files
+    pub fn isolated_pawns(&self, color: Turn) -> Vec<Position> {
+        let pawns = self.piece_list(Piece::new(color, PieceType::Pawn));
+        pawns
+            .iter()
+            .filter(|pawn| {
+                !pawns
+                    .iter()
+                    .any(|other| other.get_x().abs_diff(pawn.get_x()) == 1)
+            })
+            .copied()
+            .collect()
+    }
+
+    // pawns with no opponent's pawn in front of them on the same or neighbouring files
+    pub fn passed_pawns(&self, color: Turn) -> Vec<Position> {
+        let opponent_pawns = self.piece_list(Piece::new(color.opponent(), PieceType::Pawn));
+        self.piece_list(Piece::new(color, PieceType::Pawn))
+            .into_iter()
+            .filter(|pawn| {
+                !opponent_pawns.iter().any(|other| {
+                    let in_front = match color {
+                        Turn::WhitePlays => other.get_y() > pawn.get_y(),
+                        Turn::BlackPlays => other.get_y() < pawn.get_y(),
+                    };
+                    in_front && other.get_x().abs_diff(pawn.get_x()) <= 1
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::convert::TryFrom;
+
+    use super::*;
+
+    // white: Ke1 Rh1 a2 c2 c3 d4 g2, black: Ke8 a7 b7
+    const FEN: &str = "4k3/pp6/8/8/3P4/2P5/P1P3P1/4K2R w - - 0 1";
+
+    fn squares(names: &[&str]) -> Vec<Position> {
+        names
+            .iter()
+            .map(|name| Position::try_from(*name).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn material_and_piece_counts() {
+        let game = ChessGame::from_fen(FEN).unwrap();
+        assert_eq!(game.material(Turn::WhitePlays), 10);
+        assert_eq!(game.material(Turn::BlackPlays), 2);
+        assert_eq!(game.material_balance(), 8);
+
+        let counts = game.piece_counts(Turn::WhitePlays);
+        assert_eq!(counts.len(), 3);
+        assert_eq!(counts[&PieceType::Pawn], 5);
+        assert_eq!(counts[&PieceType::Rook], 1);
+        assert_eq!(counts[&PieceType::King], 1);
+        assert_eq!(
+            game.piece_list(Piece::Black(PieceType::Pawn)),
+            squares(&["a7", "b7"])
+        );
+    }
+
+    #[test]
+    fn mobility_of_both_players() {
+        let game = ChessGame::from_fen(FEN).unwrap();
+        // a3 a4 c4 d5 g3 g4, the rook seven up the file and two along the
+        // rank, the king five
+        assert_eq!(game.mobility(Turn::WhitePlays), 20);
+        // the king five, the pawns two each
+        assert_eq!(game.mobility(Turn::BlackPlays), 9);
+    }
+
+    #[test]
+    fn pawn_structure() {
+        let game = ChessGame::from_fen(FEN).unwrap();
+        assert_eq!(game.doubled_pawns(Turn::WhitePlays), squares(&["c2", "c3"]));
+        assert_eq!(game.doubled_pawns(Turn::BlackPlays), []);
+        assert_eq!(
+            game.isolated_pawns(Turn::WhitePlays),
+            squares(&["a2", "g2"])
+        );
+        assert_eq!(game.isolated_pawns(Turn::BlackPlays), []);
+        assert_eq!(game.passed_pawns(Turn::WhitePlays), squares(&["g2", "d4"]));
+        assert_eq!(game.passed_pawns(Turn::BlackPlays), []);
+    }
+}
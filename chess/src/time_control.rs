@@ -0,0 +1,366 @@
+use std::{cell::Cell, fmt::Debug, rc::Rc, time::Duration, time::Instant};
+
+use crate::solution::{Error, Turn};
+
+// time elapsed since an arbitrary starting point
+pub trait TimeSource: Debug {
+    fn now(&self) -> Duration;
+}
+
+#[derive(Debug)]
+pub struct SystemTimeSource {
+    start: Instant,
+}
+
+impl SystemTimeSource {
+    pub fn new() -> Self {
+        SystemTimeSource {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// time only moves when told to, for deterministic tests
+#[derive(Debug, Default)]
+pub struct ManualTimeSource {
+    now: Cell<Duration>,
+}
+
+impl ManualTimeSource {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bonus {
+    None,
+    // Fischer, added after every move
+    Increment(Duration),
+    // Bronstein, the time used up to the delay is given back after every move
+    Delay(Duration),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Period {
+    // None means the rest of the game
+    pub moves: Option<u32>,
+    pub time: Duration,
+}
+
+impl Period {
+    pub fn moves(moves: u32, time: Duration) -> Self {
+        Period {
+            moves: Some(moves),
+            time,
+        }
+    }
+    pub fn rest_of_game(time: Duration) -> Self {
+        Period { moves: None, time }
+    }
+}
+
+// e.g. 40/90 + 30:
+// TimeControl::new(vec![Period::moves(40, 90 min), Period::rest_of_game(30 min)], Bonus::None)
+// the last period is repeated when it has a number of moves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeControl {
+    periods: Vec<Period>,
+    bonus: Bonus,
+}
+
+impl TimeControl {
+    // fails without a period
+    pub fn new(periods: Vec<Period>, bonus: Bonus) -> Result<Self, Error> {
+        if periods.is_empty() {
+            return Err(Error::InvalidTimeControl);
+        }
+
+        Ok(TimeControl { periods, bonus })
+    }
+    pub fn sudden_death(time: Duration) -> Self {
+        TimeControl::single_period(time, Bonus::None)
+    }
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        TimeControl::single_period(time, Bonus::Increment(increment))
+    }
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        TimeControl::single_period(time, Bonus::Delay(delay))
+    }
+    fn single_period(time: Duration, bonus: Bonus) -> Self {
+        TimeControl {
+            periods: vec![Period::rest_of_game(time)],
+            bonus,
+        }
+    }
+    pub fn periods(&self) -> &[Period] {
+        &self.periods
+    }
+    pub fn bonus(&self) -> Bonus {
+        self.bonus
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct PlayerClock {
+    remaining: Duration,
+    period: usize,
+    moves_in_period: u32,
+}
+
+// time and periods of both players, without the running turn
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct ClockState {
+    white: PlayerClock,
+    black: PlayerClock,
+}
+
+#[derive(Debug, Clone)]
+pub struct GameClock {
+    control: TimeControl,
+    source: Rc<dyn TimeSource>,
+    white: PlayerClock,
+    black: PlayerClock,
+    running: Turn,
+    turn_started: Duration,
+}
+
+impl GameClock {
+    pub fn new(control: TimeControl, source: Rc<dyn TimeSource>) -> Self {
+        let player = PlayerClock {
+            remaining: control.periods[0].time,
+            period: 0,
+            moves_in_period: 0,
+        };
+        let turn_started = source.now();
+
+        GameClock {
+            control,
+            source,
+            white: player,
+            black: player,
+            running: Turn::WhitePlays,
+            turn_started,
+        }
+    }
+    pub fn with_system_time(control: TimeControl) -> Self {
+        GameClock::new(control, Rc::new(SystemTimeSource::new()))
+    }
+    pub fn control(&self) -> &TimeControl {
+        &self.control
+    }
+    pub fn running(&self) -> Turn {
+        self.running
+    }
+
+    // includes the time the player on turn is thinking right now
+    pub fn remaining(&self, color: Turn) -> Duration {
+        let remaining = self.player(color).remaining;
+        if color == self.running {
+            remaining.saturating_sub(self.elapsed())
+        } else {
+            remaining
+        }
+    }
+
+    // player who ran out of time
+    pub fn flagged(&self) -> Option<Turn> {
+        if self.elapsed() >= self.player(self.running).remaining {
+            Some(self.running)
+        } else {
+            None
+        }
+    }
+
+    // starts the clock of the given player
+    pub(crate) fn start(&mut self, color: Turn) {
+        self.running = color;
+        self.turn_started = self.source.now();
+    }
+
+    pub(crate) fn state(&self) -> ClockState {
+        ClockState {
+            white: self.white,
+            black: self.black,
+        }
+    }
+    pub(crate) fn restore(&mut self, state: ClockState) {
+        self.white = state.white;
+        self.black = state.black;
+    }
+
+    // the running player made a move, charge the time and start the opponent's clock
+    pub(crate) fn press(&mut self) {
+        let elapsed = self.elapsed();
+        let bonus = self.control.bonus;
+        let periods = &self.control.periods;
+        let player = match self.running {
+            Turn::WhitePlays => &mut self.white,
+            Turn::BlackPlays => &mut self.black,
+        };
+
+        player.remaining = player.remaining.saturating_sub(elapsed);
+        match bonus {
+            Bonus::None => (),
+            Bonus::Increment(increment) => player.remaining += increment,
+            Bonus::Delay(delay) => player.remaining += elapsed.min(delay),
+        }
+
+        player.moves_in_period += 1;
+        if Some(player.moves_in_period) == periods[player.period].moves {
+            player.period = (player.period + 1).min(periods.len() - 1);
+            player.moves_in_period = 0;
+            player.remaining += periods[player.period].time;
+        }
+
+        self.start(self.running.opponent());
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.source.now().saturating_sub(self.turn_started)
+    }
+
+    fn player(&self, color: Turn) -> &PlayerClock {
+        match color {
+            Turn::WhitePlays => &self.white,
+            Turn::BlackPlays => &self.black,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::movegen::Move;
+    use crate::solution::{ChessGame, Position};
+
+    fn secs(seconds: u64) -> Duration {
+        Duration::from_secs(seconds)
+    }
+
+    fn clock(control: TimeControl) -> (GameClock, Rc<ManualTimeSource>) {
+        let time = Rc::new(ManualTimeSource::new());
+        (GameClock::new(control, time.clone()), time)
+    }
+
+    fn play(game: &mut ChessGame, mv: &str) {
+        let (src, dst) = mv.split_at(2);
+        let mv = Move::new(
+            Position::try_from(src).unwrap(),
+            Position::try_from(dst).unwrap(),
+        );
+        game.play_move(mv).unwrap();
+    }
+
+    #[test]
+    fn fischer_adds_the_increment_after_the_move() {
+        let (mut clock, time) = clock(TimeControl::fischer(secs(10), secs(2)));
+        time.advance(secs(3));
+        assert_eq!(clock.remaining(Turn::WhitePlays), secs(7));
+
+        clock.press();
+        assert_eq!(clock.remaining(Turn::WhitePlays), secs(9));
+        assert_eq!(clock.remaining(Turn::BlackPlays), secs(10));
+        assert_eq!(clock.running(), Turn::BlackPlays);
+    }
+
+    #[test]
+    fn bronstein_gives_back_the_time_used_up_to_the_delay() {
+        let (mut clock, time) = clock(TimeControl::bronstein(secs(10), secs(2)));
+        time.advance(secs(3));
+        clock.press();
+        assert_eq!(clock.remaining(Turn::WhitePlays), secs(9));
+
+        time.advance(secs(1));
+        clock.press();
+        assert_eq!(clock.remaining(Turn::BlackPlays), secs(10));
+    }
+
+    #[test]
+    fn the_next_period_adds_its_time() {
+        let periods = vec![Period::moves(1, secs(10)), Period::rest_of_game(secs(5))];
+        let (mut clock, time) = clock(TimeControl::new(periods, Bonus::None).unwrap());
+        time.advance(secs(2));
+        clock.press();
+        assert_eq!(clock.remaining(Turn::WhitePlays), secs(13));
+
+        // the rest of the game adds nothing more
+        clock.press();
+        time.advance(secs(1));
+        clock.press();
+        assert_eq!(clock.remaining(Turn::WhitePlays), secs(12));
+    }
+
+    #[test]
+    fn forty_moves_in_ninety_minutes_then_thirty() {
+        let minutes = |minutes: u64| secs(60 * minutes);
+        let periods = vec![
+            Period::moves(40, minutes(90)),
+            Period::rest_of_game(minutes(30)),
+        ];
+        let (mut clock, time) = clock(TimeControl::new(periods, Bonus::None).unwrap());
+        for _ in 0..39 {
+            time.advance(minutes(1));
+            clock.press();
+            clock.press();
+        }
+        assert_eq!(clock.remaining(Turn::WhitePlays), minutes(51));
+
+        time.advance(minutes(1));
+        clock.press();
+        assert_eq!(clock.remaining(Turn::WhitePlays), minutes(80));
+    }
+
+    #[test]
+    fn undo_restores_the_time_and_the_period() {
+        let periods = vec![Period::moves(1, secs(10)), Period::rest_of_game(secs(5))];
+        let (clock, time) = clock(TimeControl::new(periods, Bonus::None).unwrap());
+        let mut game = ChessGame::new_game();
+        game.set_clock(clock);
+
+        time.advance(secs(2));
+        play(&mut game, "e2e4");
+        assert_eq!(game.clock().unwrap().remaining(Turn::WhitePlays), secs(13));
+
+        time.advance(secs(1));
+        game.undo_move().unwrap();
+        let clock = game.clock().unwrap();
+        assert_eq!(clock.running(), Turn::WhitePlays);
+        assert_eq!(clock.remaining(Turn::WhitePlays), secs(10));
+        assert_eq!(clock.remaining(Turn::BlackPlays), secs(10));
+
+        // the first period is played again
+        time.advance(secs(4));
+        play(&mut game, "d2d4");
+        assert_eq!(game.clock().unwrap().remaining(Turn::WhitePlays), secs(11));
+    }
+
+    #[test]
+    fn a_time_control_needs_a_period() {
+        assert_eq!(
+            TimeControl::new(Vec::new(), Bonus::None),
+            Err(Error::InvalidTimeControl)
+        );
+    }
+}
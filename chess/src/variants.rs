@@ -0,0 +1,318 @@
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::castling;
+use crate::movegen::{self, Move};
+use crate::solution::{
+    ChessGame, GameOutcome, GameResult, Piece, PieceType, Position, Termination, Tiles, Turn,
+};
+use crate::square::Direction;
+
+// Rules of a chess variant. The defaults are the rules of standard chess,
+// a variant overrides only what differs.
+pub trait Rules: Debug {
+    fn name(&self) -> &'static str;
+
+    // moves of the player on turn
+    fn legal_moves(&self, game: &ChessGame) -> Vec<Move> {
+        let color = game.current_player();
+        let mut moves: Vec<Move> =
+            movegen::pseudo_legal_moves(&game.tiles, color, game.setup(), game.en_passant)
+                .into_iter()
+                .filter(|mv| {
+                    let mut after = game.tiles.clone();
+                    self.apply_move(&mut after, mv);
+                    !self.in_check(&after, color)
+                })
+                .collect();
+        moves.extend(castling::castling_moves(self, game));
+
+        moves
+    }
+
+    fn in_check(&self, tiles: &Tiles, color: Turn) -> bool {
+        movegen::in_check(tiles, color)
+    }
+
+    // changes the tiles, returns the captured piece
+    fn apply_move(&self, tiles: &mut Tiles, mv: &Move) -> Option<Piece> {
+        movegen::apply_to_tiles(tiles, mv)
+    }
+
+    // checked after every move, the player on turn is the one who did not move
+    fn outcome(&self, game: &ChessGame) -> Option<GameOutcome> {
+        mate_or_stalemate(self, game)
+    }
+}
+
+// rules of the variant with the given name, case, spaces and dashes are
+// ignored, e.g. "King of the Hill" or "kingofthehill"
+pub fn rules_by_name(name: &str) -> Option<Rc<dyn Rules>> {
+    let all: [Rc<dyn Rules>; 4] = [
+        Rc::new(Standard),
+        Rc::new(KingOfTheHill),
+        Rc::new(ThreeCheck),
+        Rc::new(Atomic),
+    ];
+    all.into_iter()
+        .find(|rules| name_key(rules.name()) == name_key(name))
+}
+
+// the name as written in FEN, e.g. "kingofthehill"
+pub(crate) fn name_key(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+// checkmate or stalemate with the moves and checks of the given rules
+fn mate_or_stalemate<R: Rules + ?Sized>(rules: &R, game: &ChessGame) -> Option<GameOutcome> {
+    if !rules.legal_moves(game).is_empty() {
+        return None;
+    }
+
+    Some(if rules.in_check(&game.tiles, game.current_player()) {
+        GameOutcome {
+            result: GameResult::win_for(game.current_player().opponent()),
+            termination: Termination::Checkmate,
+        }
+    } else {
+        GameOutcome {
+            result: GameResult::Draw,
+            termination: Termination::Stalemate,
+        }
+    })
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Standard;
+
+impl Rules for Standard {
+    fn name(&self) -> &'static str {
+        "Standard"
+    }
+}
+
+// the king reaching one of the four central squares wins
+#[derive(Debug, Copy, Clone, Default)]
+pub struct KingOfTheHill;
+
+// d4, e4, d5 and e5 on the standard board
+fn hill(game: &ChessGame) -> [Position; 4] {
+    let (x, y) = ((game.width() - 1) / 2, (game.height() - 1) / 2);
+    [
+        Position { x, y },
+        Position { x: x + 1, y },
+        Position { x, y: y + 1 },
+        Position { x: x + 1, y: y + 1 },
+    ]
+}
+
+impl Rules for KingOfTheHill {
+    fn name(&self) -> &'static str {
+        "King of the Hill"
+    }
+
+    fn outcome(&self, game: &ChessGame) -> Option<GameOutcome> {
+        let mover = game.current_player().opponent();
+        let king = Piece::new(mover, PieceType::King);
+        if hill(game)
+            .iter()
+            .any(|pos| game.get_field_ref(pos) == Some(king))
+        {
+            return Some(GameOutcome {
+                result: GameResult::win_for(mover),
+                termination: Termination::KingOfTheHill,
+            });
+        }
+
+        mate_or_stalemate(self, game)
+    }
+}
+
+// giving the third check wins
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ThreeCheck;
+
+pub const THREE_CHECK_LIMIT: usize = 3;
+
+impl Rules for ThreeCheck {
+    fn name(&self) -> &'static str {
+        "Three-check"
+    }
+
+    fn outcome(&self, game: &ChessGame) -> Option<GameOutcome> {
+        let mover = game.current_player().opponent();
+        if game.checks_given(mover) >= THREE_CHECK_LIMIT {
+            return Some(GameOutcome {
+                result: GameResult::win_for(mover),
+                termination: Termination::ThreeChecks,
+            });
+        }
+
+        mate_or_stalemate(self, game)
+    }
+}
+
+// every capture explodes the capturing piece, the captured piece and all
+// pieces but pawns around, blowing up the opponent's king wins
+#[derive(Debug, Copy, Clone, Default)]
+pub struct Atomic;
+
+impl Rules for Atomic {
+    fn name(&self) -> &'static str {
+        "Atomic"
+    }
+
+    fn legal_moves(&self, game: &ChessGame) -> Vec<Move> {
+        let color = game.current_player();
+        movegen::pseudo_legal_moves(&game.tiles, color, game.setup(), game.en_passant)
+            .into_iter()
+            .filter(|mv| {
+                // the king would explode with the captured piece
+                let is_king = game.get_field_ref(&mv.src).map(|piece| piece.piece_type())
+                    == Some(PieceType::King);
+                if is_king && game.get_field_ref(&mv.dst).is_some() {
+                    return false;
+                }
+
+                let mut after = game.tiles.clone();
+                self.apply_move(&mut after, mv);
+                if movegen::find_king(&after, color).is_none() {
+                    return false;
+                }
+                if movegen::find_king(&after, color.opponent()).is_none() {
+                    return true;
+                }
+                !self.in_check(&after, color)
+            })
+            .chain(castling::castling_moves(self, game))
+            .collect()
+    }
+
+    // a king next to the opponent's king cannot be captured
+    fn in_check(&self, tiles: &Tiles, color: Turn) -> bool {
+        let kings_touch = match (
+            movegen::find_king(tiles, color),
+            movegen::find_king(tiles, color.opponent()),
+        ) {
+            (Some(own), Some(other)) => {
+                let (dx, dy) = own.distance_from(&other);
+                dx.abs() <= 1 && dy.abs() <= 1
+            }
+            _ => false,
+        };
+
+        !kings_touch && movegen::in_check(tiles, color)
+    }
+
+    fn apply_move(&self, tiles: &mut Tiles, mv: &Move) -> Option<Piece> {
+        let captured = movegen::apply_to_tiles(tiles, mv);
+        if captured.is_some() {
+            tiles[mv.dst.get_y()][mv.dst.get_x()] = None;
+            for direction in Direction::ALL {
+                if let Some(pos) = movegen::step(tiles, &mv.dst, direction) {
+                    if let Some(piece) = tiles[pos.get_y()][pos.get_x()] {
+                        if piece.piece_type() != PieceType::Pawn {
+                            tiles[pos.get_y()][pos.get_x()] = None;
+                        }
+                    }
+                }
+            }
+        }
+
+        captured
+    }
+
+    fn outcome(&self, game: &ChessGame) -> Option<GameOutcome> {
+        let mover = game.current_player().opponent();
+        if movegen::find_king(&game.tiles, game.current_player()).is_none() {
+            return Some(GameOutcome {
+                result: GameResult::win_for(mover),
+                termination: Termination::KingExploded,
+            });
+        }
+
+        mate_or_stalemate(self, game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::TryFrom;
+
+    fn pos(square: &str) -> Position {
+        Position::try_from(square).unwrap()
+    }
+
+    fn play(game: &mut ChessGame, moves: &[&str]) -> Option<Piece> {
+        let mut captured = None;
+        for mv in moves {
+            let (src, dst) = mv.split_at(2);
+            captured = game.play_move(Move::new(pos(src), pos(dst))).unwrap();
+        }
+        captured
+    }
+
+    #[test]
+    fn king_of_the_hill_is_won_on_a_central_square() {
+        let mut game = ChessGame::from_fen("4k3/8/8/8/8/3K4/8/8 w - - 0 1 kingofthehill").unwrap();
+        play(&mut game, &["d3d4"]);
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome {
+                result: GameResult::WhiteWins,
+                termination: Termination::KingOfTheHill,
+            })
+        );
+    }
+
+    #[test]
+    fn three_check_is_won_on_the_third_check() {
+        let mut game =
+            ChessGame::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1 +3+3 threecheck").unwrap();
+        play(&mut game, &["a1a8", "e8e7", "a8a7", "e7f6"]);
+        assert_eq!(game.checks_given(Turn::WhitePlays), 2);
+        assert_eq!(game.outcome(), None);
+
+        play(&mut game, &["a7a6"]);
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome {
+                result: GameResult::WhiteWins,
+                termination: Termination::ThreeChecks,
+            })
+        );
+    }
+
+    #[test]
+    fn atomic_capture_next_to_the_king_explodes_it() {
+        let mut game = ChessGame::from_fen("4k3/4q3/8/8/8/8/8/4R1K1 w - - 0 1 atomic").unwrap();
+        play(&mut game, &["e1e7"]);
+        assert_eq!(game.get_field(pos("e8")), None);
+        assert_eq!(
+            game.outcome(),
+            Some(GameOutcome {
+                result: GameResult::WhiteWins,
+                termination: Termination::KingExploded,
+            })
+        );
+    }
+
+    #[test]
+    fn atomic_capture_removes_the_pieces_around_but_pawns() {
+        let mut game = ChessGame::from_fen("4k3/8/8/2npb3/3q4/8/8/3RK3 w - - 0 1 atomic").unwrap();
+        let captured = play(&mut game, &["d1d4"]);
+        assert_eq!(captured, Some(Piece::Black(PieceType::Queen)));
+        for square in ["d1", "d4", "c5", "e5"] {
+            assert_eq!(game.get_field(pos(square)), None, "{}", square);
+        }
+        assert_eq!(
+            game.get_field(pos("d5")),
+            Some(Piece::Black(PieceType::Pawn))
+        );
+        assert_eq!(game.outcome(), None);
+    }
+}